@@ -0,0 +1,417 @@
+// Pushrod Widgets
+// Animation
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::properties::{PropertyValue, WidgetProperties};
+use crate::property_registry::{property_descriptor, PropertyKind};
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use std::time::Duration;
+
+/// This is an easing curve, which maps the linear progress of an `Animation` (from `0.0` to `1.0`)
+/// to the progress of the animated value.
+#[derive(Debug, Clone, Copy)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+
+    /// A custom curve, which should return `0.0` for `0.0`, and `1.0` for `1.0`.
+    Custom(fn(f64) -> f64),
+}
+
+impl Easing {
+    /// Applies the easing curve to the linear progress `t`.
+    pub fn apply(&self, t: f64) -> f64 {
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => t * (2.0 - t),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    -1.0 + (4.0 - 2.0 * t) * t
+                }
+            }
+            Easing::Custom(curve) => curve(t),
+        }
+    }
+}
+
+/// This describes how many times an `Animation` runs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Repeat {
+    /// The `Animation` runs once.
+    Once,
+
+    /// The `Animation` runs the given number of times.
+    Count(u32),
+
+    /// The `Animation` runs until it is stopped.
+    Forever,
+}
+
+/// This is a callback that is called with the ID of the animated `Widget` when an `Animation`
+/// completes.
+pub type AnimationCallback = Box<dyn FnMut(u32)>;
+
+/// This is an `Animation`, which changes a property of a `Widget` from one value to another over
+/// a period of time.  Colors, origins, bounds, rectangles and numeric values (such as border
+/// widths and opacity) are interpolated; any other values change to the `to` value when the
+/// `Animation` completes.  Values stored as text or lists are converted to the kind the property
+/// was registered with before they are interpolated, so a color can be animated from `"red"` to
+/// `"#0000ff"`.  `Animation`s are run by a `WidgetCache` through `WidgetCache::animate`,
+/// and advanced by calling `WidgetCache::tick`.
+pub struct Animation {
+    pub widget_id: u32,
+    pub property_key: u32,
+
+    /// The value to start from.  If `None`, the value of the property at the time the `Animation`
+    /// starts (after its `delay`) is used.
+    pub from: Option<PropertyValue>,
+    pub to: PropertyValue,
+    pub duration: Duration,
+    pub delay: Duration,
+    pub easing: Easing,
+    pub repeat: Repeat,
+    pub on_complete: Option<AnimationCallback>,
+    elapsed: Duration,
+    resolved: bool,
+}
+
+/// This is the implementation of the `Animation`.
+impl Animation {
+    /// Creates a new `Animation` that changes a property of a `Widget` from its current value to
+    /// the `to` value over the given `duration`.  The `Animation` starts without delay, runs once
+    /// using `Easing::Linear`, and has no completion callback; these can be changed by setting
+    /// the corresponding fields before the `Animation` is started.
+    pub fn new(widget_id: u32, property_key: u32, to: PropertyValue, duration: Duration) -> Self {
+        Self {
+            widget_id,
+            property_key,
+            from: None,
+            to,
+            duration,
+            delay: Duration::default(),
+            easing: Easing::Linear,
+            repeat: Repeat::Once,
+            on_complete: None,
+            elapsed: Duration::default(),
+            resolved: false,
+        }
+    }
+
+    /// Returns a flag indicating whether or not the `Animation` has started running, meaning its
+    /// `delay` has passed.
+    pub fn started(&self) -> bool {
+        self.elapsed >= self.delay
+    }
+
+    /// Advances the `Animation` by the given amount of time.  Returns the eased progress of the
+    /// current run (from `0.0` to `1.0`), or `None` if the `Animation` has not started, along with
+    /// a flag indicating whether or not it has completed.
+    pub fn advance(&mut self, elapsed: Duration) -> (Option<f64>, bool) {
+        self.elapsed += elapsed;
+
+        if !self.started() {
+            return (None, false);
+        }
+
+        let active = (self.elapsed - self.delay).as_secs_f64();
+        let duration = self.duration.as_secs_f64();
+        let runs = match self.repeat {
+            Repeat::Once => Some(1.0),
+            Repeat::Count(count) => Some(f64::from(count)),
+            Repeat::Forever => None,
+        };
+
+        if duration <= 0.0 || runs.is_some_and(|runs| active >= duration * runs) {
+            return (Some(1.0), true);
+        }
+
+        let t = (active % duration) / duration;
+
+        (Some(self.easing.apply(t)), false)
+    }
+
+    /// Advances the `Animation` by the given amount of time, and returns the value the property
+    /// should be set to, along with a flag indicating whether or not the `Animation` has
+    /// completed.  Returns `None` if the `Animation` has not started.  When the `Animation`
+    /// starts, `current` is called to retrieve the value of the property if no `from` value was
+    /// given, and both values are converted with `normalize`.
+    pub fn step<F>(&mut self, elapsed: Duration, current: F) -> Option<(PropertyValue, bool)>
+    where
+        F: FnOnce() -> Option<PropertyValue>,
+    {
+        let (progress, complete) = self.advance(elapsed);
+        let progress = progress?;
+
+        if !self.resolved {
+            let from = self
+                .from
+                .take()
+                .or_else(current)
+                .unwrap_or_else(|| self.to.clone());
+
+            self.from = Some(normalize(self.property_key, &from));
+            self.to = normalize(self.property_key, &self.to);
+            self.resolved = true;
+        }
+
+        let value = if complete {
+            self.to.clone()
+        } else {
+            interpolate(self.from.as_ref().unwrap(), &self.to, progress)
+        };
+
+        Some((value, complete))
+    }
+}
+
+/// Converts a property value stored as text or a list to the kind the property was registered
+/// with, reading it with `WidgetProperties::try_get_color`, `try_get_point`, `try_get_pair` or
+/// `try_get_value`.  Values that cannot be converted, and values of properties of any other kind,
+/// are returned unchanged.
+pub fn normalize(property_key: u32, value: &PropertyValue) -> PropertyValue {
+    let kind = match property_descriptor(property_key) {
+        Some(descriptor) => descriptor.kind,
+        None => return value.clone(),
+    };
+    let mut properties = WidgetProperties::default();
+
+    properties.set(property_key, value.clone());
+
+    let converted = match kind {
+        PropertyKind::Color => properties
+            .try_get_color(property_key)
+            .map(PropertyValue::Color),
+        PropertyKind::Point => properties
+            .try_get_point(property_key)
+            .map(|(x, y)| PropertyValue::Point(x, y)),
+        PropertyKind::Pair => properties
+            .try_get_pair(property_key)
+            .map(|(w, h)| PropertyValue::Pair(w, h)),
+        PropertyKind::Integer => properties
+            .try_get_value(property_key)
+            .map(PropertyValue::Integer),
+        _ => return value.clone(),
+    };
+
+    converted.unwrap_or_else(|_| value.clone())
+}
+
+/// Interpolates between two property values, where `t` is the progress from `0.0` to `1.0`.  Values
+/// that cannot be interpolated (or that have different types) switch from `from` to `to` once
+/// `t` reaches `1.0`.
+pub fn interpolate(from: &PropertyValue, to: &PropertyValue, t: f64) -> PropertyValue {
+    fn lerp(from: f64, to: f64, t: f64) -> f64 {
+        from + (to - from) * t
+    }
+
+    match (from, to) {
+        (PropertyValue::Integer(a), PropertyValue::Integer(b)) => {
+            PropertyValue::Integer(lerp(f64::from(*a), f64::from(*b), t).round() as i32)
+        }
+        (PropertyValue::Pair(ax, ay), PropertyValue::Pair(bx, by)) => PropertyValue::Pair(
+            lerp(f64::from(*ax), f64::from(*bx), t).round() as u32,
+            lerp(f64::from(*ay), f64::from(*by), t).round() as u32,
+        ),
+        (PropertyValue::Point(ax, ay), PropertyValue::Point(bx, by)) => PropertyValue::Point(
+            lerp(f64::from(*ax), f64::from(*bx), t).round() as i32,
+            lerp(f64::from(*ay), f64::from(*by), t).round() as i32,
+        ),
+        (PropertyValue::Color(a), PropertyValue::Color(b)) => {
+            let channel = |from: u8, to: u8| lerp(f64::from(from), f64::from(to), t).round() as u8;
+
+            PropertyValue::Color(Color::RGBA(
+                channel(a.r, b.r),
+                channel(a.g, b.g),
+                channel(a.b, b.b),
+                channel(a.a, b.a),
+            ))
+        }
+        (PropertyValue::Rect(a), PropertyValue::Rect(b)) => PropertyValue::Rect(Rect::new(
+            lerp(f64::from(a.x()), f64::from(b.x()), t).round() as i32,
+            lerp(f64::from(a.y()), f64::from(b.y()), t).round() as i32,
+            lerp(f64::from(a.width()), f64::from(b.width()), t).round() as u32,
+            lerp(f64::from(a.height()), f64::from(b.height()), t).round() as u32,
+        )),
+        _ => {
+            if t >= 1.0 {
+                to.clone()
+            } else {
+                from.clone()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::properties::{PROPERTY_BORDER_WIDTH, PROPERTY_MAIN_COLOR, PROPERTY_ORIGIN};
+
+    const STEP: Duration = Duration::from_millis(250);
+
+    #[test]
+    fn easing_curves_start_and_end_in_place() {
+        for easing in &[
+            Easing::Linear,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+        ] {
+            assert_eq!(easing.apply(0.0), 0.0);
+            assert_eq!(easing.apply(1.0), 1.0);
+        }
+
+        assert_eq!(Easing::Linear.apply(0.25), 0.25);
+        assert_eq!(Easing::EaseIn.apply(0.5), 0.25);
+        assert_eq!(Easing::EaseOut.apply(0.5), 0.75);
+        assert_eq!(Easing::EaseInOut.apply(0.25), 0.125);
+        assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+    }
+
+    #[test]
+    fn interpolate_blends_values_of_the_same_type() {
+        assert_eq!(
+            interpolate(&PropertyValue::Integer(0), &PropertyValue::Integer(10), 0.5),
+            PropertyValue::Integer(5)
+        );
+        assert_eq!(
+            interpolate(
+                &PropertyValue::Point(0, 100),
+                &PropertyValue::Point(100, 0),
+                0.25
+            ),
+            PropertyValue::Point(25, 75)
+        );
+        assert_eq!(
+            interpolate(
+                &PropertyValue::Color(Color::RGB(0, 0, 0)),
+                &PropertyValue::Color(Color::RGB(200, 100, 50)),
+                0.5
+            ),
+            PropertyValue::Color(Color::RGB(100, 50, 25))
+        );
+    }
+
+    #[test]
+    fn interpolate_switches_values_that_cannot_be_blended() {
+        let from = PropertyValue::String(String::from("a"));
+        let to = PropertyValue::String(String::from("b"));
+
+        assert_eq!(interpolate(&from, &to, 0.5), from);
+        assert_eq!(interpolate(&from, &to, 1.0), to);
+    }
+
+    #[test]
+    fn normalize_converts_values_to_the_registered_kind() {
+        assert_eq!(
+            normalize(
+                PROPERTY_MAIN_COLOR,
+                &PropertyValue::String(String::from("#ff0000"))
+            ),
+            PropertyValue::Color(Color::RGB(255, 0, 0))
+        );
+        assert_eq!(
+            normalize(
+                PROPERTY_ORIGIN,
+                &PropertyValue::List(vec![PropertyValue::Integer(3), PropertyValue::Integer(4)])
+            ),
+            PropertyValue::Point(3, 4)
+        );
+        assert_eq!(
+            normalize(
+                PROPERTY_BORDER_WIDTH,
+                &PropertyValue::String(String::from("bogus"))
+            ),
+            PropertyValue::String(String::from("bogus"))
+        );
+    }
+
+    #[test]
+    fn step_interpolates_text_colors() {
+        let mut animation = Animation::new(
+            1,
+            PROPERTY_MAIN_COLOR,
+            PropertyValue::String(String::from("#000000")),
+            STEP * 2,
+        );
+
+        animation.from = Some(PropertyValue::String(String::from("rgb(200, 100, 50)")));
+
+        assert_eq!(
+            animation.step(STEP, || None),
+            Some((PropertyValue::Color(Color::RGB(100, 50, 25)), false))
+        );
+        assert_eq!(
+            animation.step(STEP, || None),
+            Some((PropertyValue::Color(Color::RGB(0, 0, 0)), true))
+        );
+    }
+
+    #[test]
+    fn step_waits_for_the_delay_and_reads_the_current_value() {
+        let mut animation = Animation::new(
+            1,
+            PROPERTY_BORDER_WIDTH,
+            PropertyValue::Integer(8),
+            STEP * 4,
+        );
+
+        animation.delay = STEP;
+
+        assert_eq!(
+            animation.step(STEP / 2, || panic!("read before the delay")),
+            None
+        );
+        assert_eq!(
+            animation.step(STEP / 2, || Some(PropertyValue::Integer(0))),
+            Some((PropertyValue::Integer(0), false))
+        );
+        assert_eq!(
+            animation.step(STEP, || panic!("read twice")),
+            Some((PropertyValue::Integer(2), false))
+        );
+        assert_eq!(
+            animation.step(STEP * 3, || panic!("read twice")),
+            Some((PropertyValue::Integer(8), true))
+        );
+    }
+
+    #[test]
+    fn advance_repeats_the_given_number_of_times() {
+        let mut animation = Animation::new(
+            1,
+            PROPERTY_BORDER_WIDTH,
+            PropertyValue::Integer(8),
+            STEP * 2,
+        );
+
+        animation.repeat = Repeat::Count(2);
+
+        assert_eq!(animation.advance(STEP), (Some(0.5), false));
+        assert_eq!(animation.advance(STEP * 2), (Some(0.5), false));
+        assert_eq!(animation.advance(STEP), (Some(1.0), true));
+
+        let mut forever = Animation::new(1, PROPERTY_BORDER_WIDTH, PropertyValue::Integer(8), STEP);
+
+        forever.repeat = Repeat::Forever;
+
+        assert_eq!(forever.advance(STEP * 100 + STEP / 2), (Some(0.5), false));
+    }
+}
@@ -0,0 +1,424 @@
+// Pushrod Widgets
+// Background
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::colors::{parse_color, ColorError};
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use std::f64::consts::SQRT_2;
+
+/// This is the shape of a `Gradient`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GradientKind {
+    /// The colors change along a line at the given angle, in degrees, where `0` runs from the
+    /// bottom to the top, and `90` runs from the left to the right.
+    Linear(f64),
+
+    /// The colors change outwards from the center, along an ellipse that reaches the corners.
+    Radial,
+}
+
+/// This is a gradient, painted as a background of a `Widget` through
+/// `PROPERTY_BACKGROUND_GRADIENT`.  Gradients are written in the CSS format, such as
+/// `linear-gradient(90deg, red, #ffffff80 25%, blue)` or `radial-gradient(white, black)`.  A
+/// linear gradient may start with an angle (in `deg` or `turn`), or a direction such as
+/// `to right`; without one, it runs from the top to the bottom.  Each stop is a color, followed
+/// by an optional position as a percentage.  Stops without a position are spaced evenly between
+/// their neighbors.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gradient {
+    pub kind: GradientKind,
+
+    /// The color stops, as positions from `0.0` to `1.0` in increasing order.
+    pub stops: Vec<(f64, Color)>,
+}
+
+/// This is the implementation of the `Gradient`.
+impl Gradient {
+    /// Parses a `Gradient` from its CSS text.
+    pub fn parse(text: &str) -> Result<Self, ColorError> {
+        let text = text.trim().to_ascii_lowercase();
+        let open = text.find('(').ok_or(ColorError::Invalid)?;
+        let arguments = text[open + 1..]
+            .strip_suffix(')')
+            .ok_or(ColorError::Invalid)?;
+        let mut arguments = split_arguments(arguments);
+
+        let kind = match text[..open].trim() {
+            "linear-gradient" => {
+                let angle = arguments.first().and_then(|first| parse_angle(first));
+
+                if angle.is_some() {
+                    arguments.remove(0);
+                }
+
+                GradientKind::Linear(angle.unwrap_or(180.0))
+            }
+            "radial-gradient" => GradientKind::Radial,
+            _ => return Err(ColorError::Invalid),
+        };
+
+        if arguments.len() < 2 {
+            return Err(ColorError::Invalid);
+        }
+
+        let mut stops = arguments
+            .iter()
+            .map(|argument| parse_stop(argument))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // The first and last stops default to the ends of the gradient, and positions can only
+        // increase.
+        stops[0].0 = stops[0].0.or(Some(0.0));
+        stops.last_mut().unwrap().0.get_or_insert(1.0);
+
+        let mut highest = 0.0_f64;
+
+        for stop in stops.iter_mut() {
+            if let Some(position) = stop.0.as_mut() {
+                *position = position.max(highest);
+                highest = *position;
+            }
+        }
+
+        // Stops without a position are spaced evenly between the stops around them.
+        let mut index = 1;
+
+        while index < stops.len() {
+            if stops[index].0.is_none() {
+                let start = index - 1;
+                let end = (index..stops.len())
+                    .find(|end| stops[*end].0.is_some())
+                    .unwrap();
+                let from = stops[start].0.unwrap();
+                let to = stops[end].0.unwrap();
+
+                for (offset, stop) in stops[index..end].iter_mut().enumerate() {
+                    let step = (index + offset - start) as f64 / (end - start) as f64;
+
+                    stop.0 = Some(from + (to - from) * step);
+                }
+
+                index = end;
+            }
+
+            index += 1;
+        }
+
+        Ok(Self {
+            kind,
+            stops: stops
+                .into_iter()
+                .map(|(position, color)| (position.unwrap(), color))
+                .collect(),
+        })
+    }
+
+    /// Returns the color of the `Gradient` at the given position, from `0.0` to `1.0`.
+    pub fn color_at(&self, position: f64) -> Color {
+        let first = self.stops[0];
+        let last = self.stops[self.stops.len() - 1];
+
+        if position <= first.0 {
+            return first.1;
+        }
+
+        if position >= last.0 {
+            return last.1;
+        }
+
+        let end = self
+            .stops
+            .iter()
+            .position(|stop| stop.0 > position)
+            .unwrap();
+        let (from, from_color) = self.stops[end - 1];
+        let (to, to_color) = self.stops[end];
+        let t = (position - from) / (to - from);
+        let channel =
+            |a: u8, b: u8| (f64::from(a) + (f64::from(b) - f64::from(a)) * t).round() as u8;
+
+        Color::RGBA(
+            channel(from_color.r, to_color.r),
+            channel(from_color.g, to_color.g),
+            channel(from_color.b, to_color.b),
+            channel(from_color.a, to_color.a),
+        )
+    }
+
+    /// Renders the `Gradient` at the given size, as rows of RGBA bytes.
+    pub fn pixels(&self, width: u32, height: u32) -> Vec<u8> {
+        let (w, h) = (f64::from(width), f64::from(height));
+        let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+
+        for y in 0..height {
+            for x in 0..width {
+                let dx = f64::from(x) + 0.5 - w / 2.0;
+                let dy = f64::from(y) + 0.5 - h / 2.0;
+                let position = match self.kind {
+                    GradientKind::Linear(angle) => {
+                        let (sin, cos) = angle.to_radians().sin_cos();
+                        let length = (w * sin).abs() + (h * cos).abs();
+
+                        if length > 0.0 {
+                            (dx * sin - dy * cos) / length + 0.5
+                        } else {
+                            0.0
+                        }
+                    }
+                    GradientKind::Radial => {
+                        let rx = if w > 0.0 { dx / (w / 2.0) } else { 0.0 };
+                        let ry = if h > 0.0 { dy / (h / 2.0) } else { 0.0 };
+
+                        (rx * rx + ry * ry).sqrt() / SQRT_2
+                    }
+                };
+                let color = self.color_at(position);
+
+                pixels.extend_from_slice(&[color.r, color.g, color.b, color.a]);
+            }
+        }
+
+        pixels
+    }
+}
+
+/// This is the way an image background is placed within a `Widget`, as set by
+/// `PROPERTY_BACKGROUND_IMAGE_MODE`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageMode {
+    /// The image is stretched to the size of the `Widget`.
+    Stretch,
+
+    /// The image is repeated at its own size, starting from the top left corner.
+    Tile,
+
+    /// The image is drawn at its own size, in the center of the `Widget`.
+    Center,
+
+    /// The image is scaled to fit within the `Widget`, keeping its aspect ratio, and centered.
+    Fit,
+
+    /// The image is scaled to cover the `Widget`, keeping its aspect ratio, and centered.  Parts of
+    /// the image that fall outside of the `Widget` are cropped.
+    Fill,
+}
+
+/// This is the implementation of `ImageMode`.
+impl ImageMode {
+    /// Retrieves an `ImageMode` by its name (ie. `"tile"`), or `None` if the name is unknown.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "stretch" => Some(ImageMode::Stretch),
+            "tile" => Some(ImageMode::Tile),
+            "center" => Some(ImageMode::Center),
+            "fit" => Some(ImageMode::Fit),
+            "fill" => Some(ImageMode::Fill),
+            _ => None,
+        }
+    }
+
+    /// Returns the rectangles that an image of the given size is copied to, in order to place it
+    /// within a `Widget` of the given size.
+    pub fn placements(&self, image: (u32, u32), bounds: (u32, u32)) -> Vec<Rect> {
+        let (image_width, image_height) = image;
+        let (width, height) = bounds;
+
+        if image_width == 0 || image_height == 0 || width == 0 || height == 0 {
+            return Vec::new();
+        }
+
+        let centered = |w: u32, h: u32| {
+            Rect::new(
+                (width as i32 - w as i32) / 2,
+                (height as i32 - h as i32) / 2,
+                w.max(1),
+                h.max(1),
+            )
+        };
+        let scaled = |scale: f64| {
+            centered(
+                (f64::from(image_width) * scale).round() as u32,
+                (f64::from(image_height) * scale).round() as u32,
+            )
+        };
+        let scale_x = f64::from(width) / f64::from(image_width);
+        let scale_y = f64::from(height) / f64::from(image_height);
+
+        match self {
+            ImageMode::Stretch => vec![Rect::new(0, 0, width, height)],
+            ImageMode::Tile => (0..height)
+                .step_by(image_height as usize)
+                .flat_map(|y| {
+                    (0..width)
+                        .step_by(image_width as usize)
+                        .map(move |x| Rect::new(x as i32, y as i32, image_width, image_height))
+                })
+                .collect(),
+            ImageMode::Center => vec![centered(image_width, image_height)],
+            ImageMode::Fit => vec![scaled(scale_x.min(scale_y))],
+            ImageMode::Fill => vec![scaled(scale_x.max(scale_y))],
+        }
+    }
+}
+
+/// Splits the arguments of a CSS function on commas, ignoring commas inside of nested functions
+/// such as `rgb()`.
+fn split_arguments(text: &str) -> Vec<&str> {
+    let mut arguments = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+
+    for (index, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                arguments.push(text[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+
+    arguments.push(text[start..].trim());
+    arguments
+}
+
+/// Parses the angle of a linear gradient, in degrees.
+fn parse_angle(text: &str) -> Option<f64> {
+    if let Some(direction) = text.strip_prefix("to ") {
+        let mut words: Vec<&str> = direction.split_whitespace().collect();
+
+        words.sort_unstable();
+
+        return match words.as_slice() {
+            ["top"] => Some(0.0),
+            ["right", "top"] => Some(45.0),
+            ["right"] => Some(90.0),
+            ["bottom", "right"] => Some(135.0),
+            ["bottom"] => Some(180.0),
+            ["bottom", "left"] => Some(225.0),
+            ["left"] => Some(270.0),
+            ["left", "top"] => Some(315.0),
+            _ => None,
+        };
+    }
+
+    if let Some(degrees) = text.strip_suffix("deg") {
+        degrees.trim().parse().ok()
+    } else if let Some(turns) = text.strip_suffix("turn") {
+        turns.trim().parse::<f64>().ok().map(|turns| turns * 360.0)
+    } else {
+        None
+    }
+}
+
+/// Parses a color stop, made of a color and an optional percentage position.
+fn parse_stop(text: &str) -> Result<(Option<f64>, Color), ColorError> {
+    if let Some((color, position)) = text.rsplit_once(char::is_whitespace) {
+        if let Some(percentage) = position.strip_suffix('%') {
+            let percentage: f64 = percentage.parse().map_err(|_| ColorError::Invalid)?;
+
+            return Ok((Some(percentage / 100.0), parse_color(color)?));
+        }
+    }
+
+    Ok((None, parse_color(text)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: Color = Color::RGB(255, 0, 0);
+    const BLUE: Color = Color::RGB(0, 0, 255);
+
+    #[test]
+    fn parse_reads_linear_gradients() {
+        assert_eq!(
+            Gradient::parse("linear-gradient(red, blue)"),
+            Ok(Gradient {
+                kind: GradientKind::Linear(180.0),
+                stops: vec![(0.0, RED), (1.0, BLUE)],
+            })
+        );
+        assert_eq!(
+            Gradient::parse("linear-gradient(to top right, red, blue)").map(|g| g.kind),
+            Ok(GradientKind::Linear(45.0))
+        );
+        assert_eq!(
+            Gradient::parse("linear-gradient(0.25turn, red, blue)").map(|g| g.kind),
+            Ok(GradientKind::Linear(90.0))
+        );
+    }
+
+    #[test]
+    fn parse_spaces_stops_without_positions() {
+        let gradient =
+            Gradient::parse("linear-gradient(90deg, red, rgb(0, 255, 0), white 50%, black, blue)")
+                .unwrap();
+        let positions: Vec<f64> = gradient.stops.iter().map(|stop| stop.0).collect();
+
+        assert_eq!(positions, vec![0.0, 0.25, 0.5, 0.75, 1.0]);
+        assert_eq!(gradient.stops[1].1, Color::RGB(0, 255, 0));
+    }
+
+    #[test]
+    fn parse_keeps_positions_in_order() {
+        let gradient = Gradient::parse("radial-gradient(red 50%, blue 25%)").unwrap();
+
+        assert_eq!(gradient.kind, GradientKind::Radial);
+        assert_eq!(gradient.stops, vec![(0.5, RED), (0.5, BLUE)]);
+    }
+
+    #[test]
+    fn parse_rejects_invalid_gradients() {
+        assert_eq!(
+            Gradient::parse("conic-gradient(red, blue)"),
+            Err(ColorError::Invalid)
+        );
+        assert_eq!(
+            Gradient::parse("linear-gradient(red)"),
+            Err(ColorError::Invalid)
+        );
+        assert_eq!(
+            Gradient::parse("linear-gradient(red, blue"),
+            Err(ColorError::Invalid)
+        );
+        assert_eq!(
+            Gradient::parse("linear-gradient(red, bleu)"),
+            Err(ColorError::Invalid)
+        );
+    }
+
+    #[test]
+    fn color_at_blends_between_stops() {
+        let gradient = Gradient::parse("linear-gradient(red, blue)").unwrap();
+
+        assert_eq!(gradient.color_at(-1.0), RED);
+        assert_eq!(gradient.color_at(0.5), Color::RGB(128, 0, 128));
+        assert_eq!(gradient.color_at(2.0), BLUE);
+    }
+
+    #[test]
+    fn pixels_runs_in_the_gradient_direction() {
+        let gradient = Gradient::parse("linear-gradient(to right, red, blue)").unwrap();
+        let pixels = gradient.pixels(4, 1);
+
+        assert_eq!(pixels.len(), 16);
+        assert!(pixels[0] > pixels[12]);
+        assert!(pixels[2] < pixels[14]);
+    }
+}
@@ -0,0 +1,576 @@
+// Pushrod Widgets
+// Border
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::properties::{
+    Sides, WidgetProperties, PROPERTY_BORDER_COLOR, PROPERTY_BORDER_COLORS, PROPERTY_BORDER_RADIUS,
+    PROPERTY_BORDER_STYLE,
+};
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+
+/// This is the style used to paint a border, as set by `PROPERTY_BORDER_STYLE`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BorderStyle {
+    /// No border is drawn, regardless of the border widths.
+    None,
+
+    /// The border is drawn as a solid line.
+    Solid,
+
+    /// The border is drawn as dashes, three times as long as the border is wide.
+    Dashed,
+
+    /// The border is drawn as square dots, as long as the border is wide.
+    Dotted,
+
+    /// The top and left sides are darkened, and the bottom and right sides lightened, so the
+    /// `Widget` appears to be pressed in.
+    Inset,
+
+    /// The top and left sides are lightened, and the bottom and right sides darkened, so the
+    /// `Widget` appears to be raised.
+    Outset,
+}
+
+/// This is the implementation of `BorderStyle`.
+impl BorderStyle {
+    /// Retrieves a `BorderStyle` by its name (ie. `"dashed"`), or `None` if the name is unknown.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "none" => Some(BorderStyle::None),
+            "solid" => Some(BorderStyle::Solid),
+            "dashed" => Some(BorderStyle::Dashed),
+            "dotted" => Some(BorderStyle::Dotted),
+            "inset" => Some(BorderStyle::Inset),
+            "outset" => Some(BorderStyle::Outset),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Side {
+    Top,
+    Right,
+    Bottom,
+    Left,
+}
+
+/// This is the border of a `Widget`, with a width and color for each side, a corner radius, and
+/// a style.  The geometry of the border is computed by `paint`, which returns the rectangles to
+/// fill, so that it can be drawn into any `Canvas`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Border {
+    pub widths: Sides<u32>,
+    pub colors: Sides<Color>,
+    pub radius: u32,
+    pub style: BorderStyle,
+}
+
+/// This is the implementation of the `Border`.
+impl Border {
+    /// Reads a `Border` from the properties of a `Widget`, with the widths returned by
+    /// `WidgetProperties::get_border_widths`.  `PROPERTY_BORDER_COLORS` takes precedence over
+    /// `PROPERTY_BORDER_COLOR`, which applies the same color to every side.  An unknown
+    /// `PROPERTY_BORDER_STYLE` is drawn as `BorderStyle::Solid`.
+    pub fn from_properties(properties: &WidgetProperties) -> Self {
+        let widths = properties.get_border_widths();
+        let colors = properties
+            .try_get_side_colors(PROPERTY_BORDER_COLORS)
+            .unwrap_or_else(|_| {
+                Sides::uniform(properties.get_color(PROPERTY_BORDER_COLOR, Color::RGB(0, 0, 0)))
+            });
+        let style = BorderStyle::from_name(&properties.get_string(PROPERTY_BORDER_STYLE))
+            .unwrap_or(BorderStyle::Solid);
+        let width = |value: i32| {
+            if style == BorderStyle::None {
+                0
+            } else {
+                value as u32
+            }
+        };
+
+        Self {
+            widths: Sides {
+                top: width(widths.top),
+                right: width(widths.right),
+                bottom: width(widths.bottom),
+                left: width(widths.left),
+            },
+            colors,
+            radius: properties.get_value(PROPERTY_BORDER_RADIUS).max(0) as u32,
+            style,
+        }
+    }
+
+    /// Computes the rectangles to fill in order to paint a `Widget` of the given size with the
+    /// `fill` color and this `Border`, grouped by color.  Pixels outside of rounded corners are
+    /// painted transparent.  Pixels within the border, and in the gaps between dashes or dots, are
+    /// painted with `fill`; if it is `None`, they are left untouched, so that a background that has
+    /// already been drawn shows through.
+    ///
+    /// The straight edges of the border and the area within it are painted as whole rectangles;
+    /// only the boxes in the corners, where the sides meet and may be rounded, are computed pixel
+    /// by pixel.  A border that is wider or taller than the `Widget` is computed pixel by pixel
+    /// throughout.
+    pub fn paint(&self, fill: Option<Color>, width: u32, height: u32) -> Vec<(Color, Vec<Rect>)> {
+        let mut groups: Vec<(Color, Vec<Rect>)> = Vec::new();
+        let widths = self.widths;
+
+        if width == 0 || height == 0 {
+            return groups;
+        }
+
+        if self.radius == 0
+            && widths.top == 0
+            && widths.right == 0
+            && widths.bottom == 0
+            && widths.left == 0
+        {
+            push(&mut groups, fill, (0, 0, width, height));
+            return groups;
+        }
+
+        if widths.left.saturating_add(widths.right) > width
+            || widths.top.saturating_add(widths.bottom) > height
+        {
+            self.paint_pixels(fill, width, height, (0, 0, width, height), &mut groups);
+            return groups;
+        }
+
+        // The corner boxes cover the rounded corners, and the areas in which the sides meet.
+        let radius = self.radius.min((width + 1) / 2).min((height + 1) / 2);
+        let x0 = radius.max(widths.left).min(width);
+        let x1 = width.saturating_sub(radius.max(widths.right)).max(x0);
+        let y0 = radius.max(widths.top).min(height);
+        let y1 = height.saturating_sub(radius.max(widths.bottom)).max(y0);
+
+        for &(x, right) in &[(0, x0), (x1, width)] {
+            for &(y, bottom) in &[(0, y0), (y1, height)] {
+                self.paint_pixels(
+                    fill,
+                    width,
+                    height,
+                    (x, y, right - x, bottom - y),
+                    &mut groups,
+                );
+            }
+        }
+
+        // Between the corner boxes, the columns above and below the middle are all split the same
+        // way from top to bottom, and the rows in the middle are all split the same way from left
+        // to right: into a side, the area within the border, and the opposite side.
+        for &(top, bottom) in &[(0, y0), (y1, height)] {
+            let rows = split(top, bottom, widths.top, height - widths.bottom);
+
+            for (side, (y, h)) in [Some(Side::Top), None, Some(Side::Bottom)]
+                .iter()
+                .zip(rows.iter())
+            {
+                self.paint_area(*side, fill, (x0, *y, x1 - x0, *h), &mut groups);
+            }
+        }
+
+        let columns = split(0, width, widths.left, width - widths.right);
+
+        for (side, (x, w)) in [Some(Side::Left), None, Some(Side::Right)]
+            .iter()
+            .zip(columns.iter())
+        {
+            self.paint_area(*side, fill, (*x, y0, *w, y1 - y0), &mut groups);
+        }
+
+        groups
+    }
+
+    /// Paints an area of a `Widget`, given as `(x, y, width, height)`, pixel by pixel.
+    fn paint_pixels(
+        &self,
+        fill: Option<Color>,
+        width: u32,
+        height: u32,
+        (left, top, w, h): (u32, u32, u32, u32),
+        groups: &mut Vec<(Color, Vec<Rect>)>,
+    ) {
+        for y in top..top + h {
+            let mut x = left;
+
+            while x < left + w {
+                let start = x;
+                let color = self.pixel_color(fill, width, height, x, y);
+
+                x += 1;
+
+                while x < left + w && self.pixel_color(fill, width, height, x, y) == color {
+                    x += 1;
+                }
+
+                push(groups, color, (start, y, x - start, 1));
+            }
+        }
+    }
+
+    /// Paints an area of a `Widget` that lies entirely within a straight section of the given
+    /// side, or within the border if `side` is `None`.  Dashes and dots are painted as one
+    /// rectangle each.
+    fn paint_area(
+        &self,
+        side: Option<Side>,
+        fill: Option<Color>,
+        (x, y, w, h): (u32, u32, u32, u32),
+        groups: &mut Vec<(Color, Vec<Rect>)>,
+    ) {
+        let side = match side {
+            Some(side) => side,
+            None => return push(groups, fill, (x, y, w, h)),
+        };
+        let length = match self.style {
+            BorderStyle::Dashed => (self.side_width(side) * 3).max(3),
+            BorderStyle::Dotted => self.side_width(side).max(1),
+            _ => return push(groups, self.side_color(side, fill, x, y), (x, y, w, h)),
+        };
+        let horizontal = matches!(side, Side::Top | Side::Bottom);
+        let (start, end) = if horizontal { (x, x + w) } else { (y, y + h) };
+        let mut position = start;
+
+        while position < end {
+            let next = ((position / length + 1) * length).min(end);
+            let area = if horizontal {
+                (position, y, next - position, h)
+            } else {
+                (x, position, w, next - position)
+            };
+
+            push(groups, self.side_color(side, fill, area.0, area.1), area);
+            position = next;
+        }
+    }
+
+    /// Returns the width of the given side.
+    fn side_width(&self, side: Side) -> u32 {
+        match side {
+            Side::Top => self.widths.top,
+            Side::Right => self.widths.right,
+            Side::Bottom => self.widths.bottom,
+            Side::Left => self.widths.left,
+        }
+    }
+
+    /// Returns the color of a single pixel, or `None` if the pixel is left untouched.
+    fn pixel_color(
+        &self,
+        fill: Option<Color>,
+        width: u32,
+        height: u32,
+        x: u32,
+        y: u32,
+    ) -> Option<Color> {
+        let (w, h) = (f64::from(width), f64::from(height));
+        let (px, py) = (f64::from(x) + 0.5, f64::from(y) + 0.5);
+        let radius = f64::from(self.radius).min(w / 2.0).min(h / 2.0);
+
+        if !inside_rounded_rect(px, py, (0.0, 0.0, w, h), [(radius, radius); 4]) {
+            return Some(Color::RGBA(0, 0, 0, 0));
+        }
+
+        let top = f64::from(self.widths.top);
+        let right = f64::from(self.widths.right);
+        let bottom = f64::from(self.widths.bottom);
+        let left = f64::from(self.widths.left);
+        let inner = (left, top, w - right, h - bottom);
+        let inner_radius = |horizontal: f64, vertical: f64| {
+            (
+                (radius - horizontal).clamp(0.0, ((inner.2 - inner.0) / 2.0).max(0.0)),
+                (radius - vertical).clamp(0.0, ((inner.3 - inner.1) / 2.0).max(0.0)),
+            )
+        };
+        let inner_radii = [
+            inner_radius(left, top),
+            inner_radius(right, top),
+            inner_radius(right, bottom),
+            inner_radius(left, bottom),
+        ];
+
+        if inside_rounded_rect(px, py, inner, inner_radii) {
+            return fill;
+        }
+
+        // The pixel belongs to the side it is closest to, relative to the width of that side, so
+        // that sides of different widths meet along the diagonal of each corner.
+        let distance = |offset: f64, side_width: f64| {
+            if side_width > 0.0 {
+                offset / side_width
+            } else {
+                f64::INFINITY
+            }
+        };
+        let side = [
+            (Side::Top, distance(py, top)),
+            (Side::Right, distance(w - px, right)),
+            (Side::Bottom, distance(h - py, bottom)),
+            (Side::Left, distance(px, left)),
+        ]
+        .iter()
+        .fold((Side::Top, f64::INFINITY), |nearest, candidate| {
+            if candidate.1 < nearest.1 {
+                *candidate
+            } else {
+                nearest
+            }
+        })
+        .0;
+
+        self.side_color(side, fill, x, y)
+    }
+
+    /// Returns the color of a pixel that belongs to the given side, according to the style.
+    fn side_color(&self, side: Side, fill: Option<Color>, x: u32, y: u32) -> Option<Color> {
+        let side_width = self.side_width(side);
+        let (color, position) = match side {
+            Side::Top => (self.colors.top, x),
+            Side::Right => (self.colors.right, y),
+            Side::Bottom => (self.colors.bottom, x),
+            Side::Left => (self.colors.left, y),
+        };
+        let raised = matches!(side, Side::Top | Side::Left);
+
+        match self.style {
+            BorderStyle::None => fill,
+            BorderStyle::Solid => Some(color),
+            BorderStyle::Dashed | BorderStyle::Dotted => {
+                let length = if self.style == BorderStyle::Dashed {
+                    (side_width * 3).max(3)
+                } else {
+                    side_width.max(1)
+                };
+
+                if (position / length) % 2 == 0 {
+                    Some(color)
+                } else {
+                    fill
+                }
+            }
+            BorderStyle::Inset => Some(shade(color, !raised)),
+            BorderStyle::Outset => Some(shade(color, raised)),
+        }
+    }
+}
+
+/// Adds a rectangle, given as `(x, y, width, height)`, to the group of its color.  Empty
+/// rectangles, and rectangles without a color, are skipped.
+fn push(
+    groups: &mut Vec<(Color, Vec<Rect>)>,
+    color: Option<Color>,
+    (x, y, w, h): (u32, u32, u32, u32),
+) {
+    let color = match color {
+        Some(color) if w > 0 && h > 0 => color,
+        _ => return,
+    };
+    let rect = Rect::new(x as i32, y as i32, w, h);
+
+    match groups.iter_mut().find(|(group, _)| *group == color) {
+        Some((_, rects)) => rects.push(rect),
+        None => groups.push((color, vec![rect])),
+    }
+}
+
+/// Splits the span from `start` to `end` at `first` and `last`, returning the position and length
+/// of the part before `first`, the part between them, and the part after `last`.
+fn split(start: u32, end: u32, first: u32, last: u32) -> [(u32, u32); 3] {
+    let first = first.clamp(start, end);
+    let last = last.clamp(first, end);
+
+    [
+        (start, first - start),
+        (first, last - first),
+        (last, end - last),
+    ]
+}
+
+/// Lightens or darkens a color, keeping its alpha.
+fn shade(color: Color, lighten: bool) -> Color {
+    let channel = |value: u8| {
+        if lighten {
+            value + ((255 - value) as f64 * 0.4) as u8
+        } else {
+            (value as f64 * 0.6) as u8
+        }
+    };
+
+    Color::RGBA(
+        channel(color.r),
+        channel(color.g),
+        channel(color.b),
+        color.a,
+    )
+}
+
+/// Returns whether or not a point lies within a rectangle (given as left, top, right and bottom
+/// edges) with elliptical corners, whose radii are given clockwise from the top left corner.
+pub(crate) fn inside_rounded_rect(
+    px: f64,
+    py: f64,
+    (left, top, right, bottom): (f64, f64, f64, f64),
+    radii: [(f64, f64); 4],
+) -> bool {
+    if px < left || px > right || py < top || py > bottom {
+        return false;
+    }
+
+    let corners = [
+        (
+            left + radii[0].0,
+            top + radii[0].1,
+            radii[0],
+            px < left + radii[0].0 && py < top + radii[0].1,
+        ),
+        (
+            right - radii[1].0,
+            top + radii[1].1,
+            radii[1],
+            px > right - radii[1].0 && py < top + radii[1].1,
+        ),
+        (
+            right - radii[2].0,
+            bottom - radii[2].1,
+            radii[2],
+            px > right - radii[2].0 && py > bottom - radii[2].1,
+        ),
+        (
+            left + radii[3].0,
+            bottom - radii[3].1,
+            radii[3],
+            px < left + radii[3].0 && py > bottom - radii[3].1,
+        ),
+    ];
+
+    corners
+        .iter()
+        .filter(|(_, _, (rx, ry), within)| *within && *rx > 0.0 && *ry > 0.0)
+        .all(|(cx, cy, (rx, ry), _)| {
+            let (dx, dy) = ((px - cx) / rx, (py - cy) / ry);
+
+            dx * dx + dy * dy <= 1.0
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILL: Color = Color::RGB(200, 200, 200);
+
+    fn border(widths: [u32; 4], radius: u32, style: BorderStyle) -> Border {
+        Border {
+            widths: Sides {
+                top: widths[0],
+                right: widths[1],
+                bottom: widths[2],
+                left: widths[3],
+            },
+            colors: Sides {
+                top: Color::RGB(255, 0, 0),
+                right: Color::RGB(0, 255, 0),
+                bottom: Color::RGB(0, 0, 255),
+                left: Color::RGB(255, 255, 0),
+            },
+            radius,
+            style,
+        }
+    }
+
+    /// Checks that `paint` covers every pixel at most once, with the color computed pixel by pixel.
+    fn assert_matches_pixels(border: &Border, fill: Option<Color>, width: u32, height: u32) {
+        let mut painted = vec![None; (width * height) as usize];
+
+        for (color, rects) in border.paint(fill, width, height) {
+            for rect in rects {
+                for y in rect.top()..rect.bottom() {
+                    for x in rect.left()..rect.right() {
+                        let pixel = &mut painted[(y as u32 * width + x as u32) as usize];
+
+                        assert_eq!(*pixel, None, "{:?} painted twice at {}, {}", border, x, y);
+                        *pixel = Some(color);
+                    }
+                }
+            }
+        }
+
+        for y in 0..height {
+            for x in 0..width {
+                assert_eq!(
+                    painted[(y * width + x) as usize],
+                    border.pixel_color(fill, width, height, x, y),
+                    "{:?} at {}, {} of {}x{}",
+                    border,
+                    x,
+                    y,
+                    width,
+                    height
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn paint_matches_pixel_colors() {
+        let styles = [
+            BorderStyle::None,
+            BorderStyle::Solid,
+            BorderStyle::Dashed,
+            BorderStyle::Dotted,
+            BorderStyle::Inset,
+            BorderStyle::Outset,
+        ];
+        let widths = [[0, 0, 0, 0], [1, 1, 1, 1], [2, 5, 0, 3], [6, 1, 4, 9]];
+
+        for style in styles.iter() {
+            for side_widths in widths.iter() {
+                for radius in [0, 3, 8, 100].iter() {
+                    let border = border(*side_widths, *radius, *style);
+
+                    for &(width, height) in &[(40, 24), (17, 13), (10, 10), (12, 3), (1, 1)] {
+                        assert_matches_pixels(&border, Some(FILL), width, height);
+                        assert_matches_pixels(&border, None, width, height);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn paint_uses_whole_rects_for_straight_edges() {
+        let border = border([2, 2, 2, 2], 0, BorderStyle::Solid);
+        let rects: usize = border
+            .paint(Some(FILL), 200, 100)
+            .iter()
+            .map(|(_, rects)| rects.len())
+            .sum();
+
+        assert!(rects <= 4 * 2 * 2 + 5, "{} rects", rects);
+    }
+
+    #[test]
+    fn paint_fills_without_a_border() {
+        let border = border([0, 0, 0, 0], 0, BorderStyle::Solid);
+
+        assert_eq!(
+            border.paint(Some(FILL), 30, 20),
+            vec![(FILL, vec![Rect::new(0, 0, 30, 20)])]
+        );
+        assert!(border.paint(None, 30, 20).is_empty());
+    }
+}
@@ -16,23 +16,52 @@
 // TODO: This should probably be a draw tree, but it needs to store the top-down representation
 // TODO: of the structure.  So, a tree is not entirely accurate.
 
-use crate::properties::{PROPERTY_HIDDEN, PROPERTY_INVALIDATED};
+use crate::animation::Animation;
+use crate::event::{DragGesture, EventContext, EventPhase, WidgetEvent};
+use crate::history::{History, HistoryEntry};
+use crate::layout::{LayoutItem, Placement, Size};
+use crate::properties::{
+    ChangedProperty, PropertyTransaction, PropertyValue, WidgetProperties, PROPERTY_ANCHORS,
+    PROPERTY_BORDER_STYLE, PROPERTY_BORDER_WIDTH, PROPERTY_BORDER_WIDTHS, PROPERTY_DISABLED,
+    PROPERTY_FOCUSABLE, PROPERTY_FOCUSED, PROPERTY_FONT_NAME, PROPERTY_FONT_SIZE, PROPERTY_HIDDEN,
+    PROPERTY_HOVERED, PROPERTY_INVALIDATED, PROPERTY_MARGIN, PROPERTY_OPACITY, PROPERTY_ORIGIN,
+    PROPERTY_RELATIVE_ORIGIN, PROPERTY_RELATIVE_SIZE, PROPERTY_SIZE, PROPERTY_TAB_INDEX,
+    PROPERTY_TEXT_COLOR,
+};
+use crate::property_registry::{invalidation_of, Invalidation};
+use crate::shadow::Shadow;
 use crate::system_widgets::base_widget::BaseWidget;
+use crate::theme::Theme;
 use crate::widget::Widget;
+use sdl2::event::{Event, WindowEvent};
 use sdl2::image::LoadTexture;
-use sdl2::rect::Rect;
-use sdl2::render::{Canvas, Texture, TextureQuery};
-use sdl2::ttf::{FontStyle, Sdl2TtfContext};
+use sdl2::keyboard::{Keycode, Mod};
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::rect::{Point, Rect};
+use sdl2::render::{BlendMode, Canvas, Texture};
+use sdl2::ttf::Sdl2TtfContext;
 use sdl2::video::Window;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
+use std::time::Duration;
+
+/// This is the shadow of a `Widget`, cached until the size of the `Widget` or its shadow changes.
+struct CachedShadow {
+    shadow: Shadow,
+    size: (u32, u32),
+    texture: Texture,
+}
 
 struct WidgetCacheContainer {
     widget: RefCell<Box<dyn Widget>>,
     name: String,
     parent: u32,
     children: Vec<u32>,
+    detached: bool,
+    needs_layout: bool,
+    shadow: Option<CachedShadow>,
 }
 
 impl WidgetCacheContainer {
@@ -42,14 +71,58 @@ impl WidgetCacheContainer {
             name,
             parent,
             children: Vec::new(),
+            detached: false,
+            needs_layout: true,
+            shadow: None,
         }
     }
+
+    /// Destroys the cached shadow, if there is one.  Textures are not freed when they are dropped,
+    /// so the shadow must be cleared through here whenever it is replaced or no longer drawn.
+    fn clear_shadow(&mut self) {
+        if let Some(cached) = self.shadow.take() {
+            unsafe { cached.texture.destroy() };
+        }
+    }
+}
+
+/// This is a description of a change to a property of a `Widget` stored in the `WidgetCache`.
+/// A value of `None` indicates that the property was not set before the change, or has been
+/// deleted by it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertyChange {
+    pub widget_id: u32,
+    pub property_key: u32,
+    pub old_value: Option<PropertyValue>,
+    pub new_value: Option<PropertyValue>,
+}
+
+/// This is a callback that is called by the `WidgetCache` when a property it observes is changed.
+pub type PropertySubscriber = Box<dyn FnMut(&PropertyChange)>;
+
+struct PropertySubscription {
+    subscription_id: u32,
+    widget_id: Option<u32>,
+    callback: PropertySubscriber,
 }
 
 /// This is the `WidgetCache` store structure.
 pub struct WidgetCache {
     cache: Vec<WidgetCacheContainer>,
     texture_cache: TextureCache,
+    subscriptions: Vec<PropertySubscription>,
+    next_subscription_id: u32,
+    theme: Theme,
+    inheritable: HashSet<u32>,
+    animations: Vec<(u32, Animation)>,
+    next_animation_id: u32,
+    history: Option<History>,
+    focused_id: Option<u32>,
+    pointer: Option<(i32, i32)>,
+    hovered_id: Option<u32>,
+    captured_id: Option<u32>,
+    pressed: Option<(MouseButton, (i32, i32))>,
+    drag: Option<DragGesture>,
 }
 
 /// This is the `WidgetCache` that is used to store `Widget` references in a drawing tree by ID.
@@ -62,7 +135,7 @@ impl WidgetCache {
         eprintln!("WidgetCache::new");
         let mut base_widget = BaseWidget::default();
 
-        &base_widget.properties().set_bounds(w, h);
+        base_widget.properties().set_bounds(w, h);
 
         Self {
             cache: vec![WidgetCacheContainer::new(
@@ -71,63 +144,494 @@ impl WidgetCache {
                 0,
             )],
             texture_cache: TextureCache::default(),
+            subscriptions: Vec::new(),
+            next_subscription_id: 0,
+            theme: Theme::default(),
+            inheritable: [PROPERTY_FONT_NAME, PROPERTY_FONT_SIZE, PROPERTY_TEXT_COLOR]
+                .iter()
+                .copied()
+                .collect(),
+            animations: Vec::new(),
+            next_animation_id: 0,
+            history: None,
+            focused_id: None,
+            pointer: None,
+            hovered_id: None,
+            captured_id: None,
+            pressed: None,
+            drag: None,
         }
     }
 
-    /// Retrieves the ID of the widget at the X/Y coordinates given.
+    /// Retrieves the ID of the widget at the X/Y coordinates given.  Coordinates may be negative,
+    /// as `Widget`s may be positioned partially outside of the visible area.
     ///
     /// Follows the following rules:
     /// - If the object is hidden, any objects underneath that object are short-circuited
+    /// - Each object is clipped to the visible area of its parent, so only the visible portion of
+    ///   a partially visible object can be hit
     /// - If an object is visible, it walks the object's children to see if they are within the same
-    ///   given coordinates
+    ///   given coordinates, with children taking precedence over their parent, and later siblings
+    ///   taking precedence over earlier siblings, matching the order in which they are drawn
     ///
     /// The found ID is then returned having met all of those criteria.  If no ID was found, a
     /// 0 value (root level widget) is returned.
     #[inline]
-    pub fn id_at_point(&self, x: u32, y: u32) -> u32 {
-        let mut found_id: u32 = 0;
-        let mut hidden_widgets: Vec<u32> = vec![];
-        let children_of_widget = self.get_children_of(0);
-
-        for id in &children_of_widget {
-            // eprintln!(
-            //     "WidgetCache::id_at_point cache.length: {}, id: {}",
-            //     self.cache.len(),
-            //     id
-            // );
-            if self.cache.len() > *id as usize {
-                let is_hidden = self.cache[*id as usize]
-                    .widget
-                    .borrow_mut()
-                    .properties()
-                    .get_bool(PROPERTY_HIDDEN);
-                let widget_xy = self.cache[*id as usize]
-                    .widget
-                    .borrow_mut()
-                    .properties()
-                    .get_origin();
-                let widget_wh = self.cache[*id as usize]
+    pub fn id_at_point(&self, x: i32, y: i32) -> u32 {
+        self.find_id_at_point(0, Point::new(x, y), self.widget_rect(0))
+            .unwrap_or(0)
+    }
+
+    /// Dispatches an SDL2 `Event` to the `Widget`s in the cache, returning `true` if a `Widget`
+    /// consumed it.  Mouse button and motion events are targeted at the `Widget` under the pointer,
+    /// as found by `id_at_point`, and mouse wheel events at the `Widget` under the last known
+    /// position of the pointer.  Keyboard and text input events are targeted at the focused
+    /// `Widget`.  Other events are ignored, apart from the window events described below.  See
+    /// `dispatch_to` for the order in which the event is passed to each `Widget`.
+    ///
+    /// Mouse events also track the `Widget` under the pointer, sending enter and leave events as
+    /// the pointer moves between `Widget`s, before the event itself is dispatched.  The pointer
+    /// leaves all `Widget`s when it leaves the window.  When the window is resized, the root
+    /// `Widget` is resized to match, through `resize`.
+    ///
+    /// While a `Widget` has captured the pointer, all mouse events are targeted at it, and it
+    /// remains the hovered `Widget`.  Drag events are sent to it after the mouse event that caused
+    /// them, and the pointer is released when the mouse button is released.  In order to receive
+    /// mouse events while the pointer is outside of the window, SDL2's mouse capture must be
+    /// enabled by the application.
+    ///
+    /// Pressing a mouse button moves the keyboard focus to the innermost focusable `Widget` under
+    /// the pointer, or removes it if there is none.  Tab and Shift-Tab move the focus through the
+    /// `tab_order`, unless the focused `Widget` consumes the key press itself.
+    pub fn dispatch(&mut self, event: &Event) -> bool {
+        if let Event::Window { win_event, .. } = event {
+            match *win_event {
+                WindowEvent::Leave if self.captured_id.is_none() => {
+                    self.pointer = None;
+                    self.set_hovered(None);
+                }
+                WindowEvent::Resized(width, height) | WindowEvent::SizeChanged(width, height) => {
+                    self.resize(width.max(0) as u32, height.max(0) as u32);
+                }
+                _ => {}
+            }
+
+            return false;
+        }
+
+        let event = match WidgetEvent::from_sdl_event(event) {
+            Some(event) => event,
+            None => return false,
+        };
+
+        if let Some((x, y)) = event.position() {
+            self.pointer = Some((x, y));
+
+            let hovered_id = self.captured_id.unwrap_or_else(|| self.id_at_point(x, y));
+
+            self.set_hovered(Some(hovered_id));
+        }
+
+        if let WidgetEvent::MouseDown { button, x, y, .. } = event {
+            self.pressed = Some((button, (x, y)));
+
+            let hovered_id = self.hovered_id.unwrap_or(0);
+            let focus_id = std::iter::once(hovered_id)
+                .chain(self.ancestors_of(hovered_id))
+                .find(|widget_id| self.is_focusable(*widget_id));
+
+            self.set_focused(focus_id);
+        }
+
+        let target_id = match event {
+            WidgetEvent::KeyDown { .. }
+            | WidgetEvent::KeyUp { .. }
+            | WidgetEvent::TextInput { .. } => self.focused_id().unwrap_or(0),
+            _ => self.captured_id.or(self.hovered_id).unwrap_or(0),
+        };
+        let mut consumed = self.dispatch_to(target_id, &event);
+
+        match event {
+            WidgetEvent::MouseMove { x, y, .. } => {
+                let drag_events = match (self.captured_id, self.drag.as_mut()) {
+                    (Some(captured_id), Some(drag)) => Some((captured_id, drag.moved(x, y))),
+                    _ => None,
+                };
+
+                if let Some((captured_id, drag_events)) = drag_events {
+                    for drag_event in drag_events {
+                        consumed |= self.dispatch_to(captured_id, &drag_event);
+                    }
+                }
+            }
+            WidgetEvent::MouseUp { button, x, y, .. }
+                if self.pressed.map(|(pressed, _)| pressed) == Some(button) =>
+            {
+                self.pressed = None;
+
+                let drag_end = self.drag.and_then(|drag| drag.released(x, y));
+
+                if let (Some(captured_id), Some(drag_end)) = (self.captured_id, drag_end) {
+                    consumed |= self.dispatch_to(captured_id, &drag_end);
+                }
+
+                self.release_pointer();
+            }
+            WidgetEvent::KeyDown {
+                keycode: Some(Keycode::Tab),
+                keymod,
+                ..
+            } if !consumed => {
+                if keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD) {
+                    self.focus_previous();
+                } else {
+                    self.focus_next();
+                }
+
+                consumed = true;
+            }
+            _ => {}
+        }
+
+        consumed
+    }
+
+    /// Captures the pointer for the `Widget` with the given ID, so that all mouse events are sent
+    /// to it until the pointer is released.  If a mouse button is held down, the `Widget` is sent
+    /// drag events as the pointer moves, using a `DragGesture` that starts where the button was
+    /// pressed, with the `Widget`'s `PROPERTY_DRAG_THRESHOLD` (see `DragGesture::from_properties`).
+    /// `Widget`s usually capture the pointer through `EventContext::capture_pointer` when handling
+    /// a mouse button press.
+    pub fn capture_pointer(&mut self, widget_id: u32) {
+        let widget = &self.cache[widget_id as usize].widget;
+
+        self.drag = self.pressed.map(|(button, start)| {
+            DragGesture::from_properties(button, start, widget.borrow_mut().properties())
+        });
+        self.captured_id = Some(widget_id);
+
+        if self.pointer.is_some() {
+            self.set_hovered(Some(widget_id));
+        }
+    }
+
+    /// Releases the pointer, if it has been captured, so that mouse events are targeted at the
+    /// `Widget` under the pointer again.  A drag in progress ends without a
+    /// `WidgetEvent::DragEnd`.
+    pub fn release_pointer(&mut self) {
+        if self.captured_id.take().is_none() {
+            return;
+        }
+
+        self.drag = None;
+
+        let hovered_id = self.pointer.map(|(x, y)| self.id_at_point(x, y));
+
+        self.set_hovered(hovered_id);
+    }
+
+    /// Retrieves the ID of the `Widget` that has captured the pointer, or `None` if the pointer
+    /// has not been captured.
+    pub fn captured_id(&self) -> Option<u32> {
+        self.captured_id
+    }
+
+    /// Moves the pointer onto the `Widget` with the given ID, or off of all `Widget`s if `None`.
+    /// The hovered `Widget` and all of its ancestors have `PROPERTY_HOVERED` set, so that a
+    /// container is hovered while the pointer is over any of its children.  Each `Widget` the
+    /// pointer moves off of is sent a `WidgetEvent::Leave`, innermost first, then each `Widget`
+    /// it moves onto is sent a `WidgetEvent::Enter`, outermost first.  These events are not passed
+    /// on to the ancestors of the `Widget`.
+    fn set_hovered(&mut self, hovered_id: Option<u32>) {
+        if hovered_id == self.hovered_id {
+            return;
+        }
+
+        let chain = |cache: &Self, widget_id: Option<u32>| -> Vec<u32> {
+            widget_id
+                .map(|widget_id| {
+                    let mut chain = vec![widget_id];
+
+                    chain.extend(cache.ancestors_of(widget_id));
+                    chain
+                })
+                .unwrap_or_default()
+        };
+        let old_chain = chain(self, self.hovered_id);
+        let new_chain = chain(self, hovered_id);
+        let history = self.history.take();
+
+        self.hovered_id = hovered_id;
+
+        for widget_id in old_chain.iter().filter(|id| !new_chain.contains(id)) {
+            self.delete_property(*widget_id, PROPERTY_HOVERED);
+            self.deliver(
+                *widget_id,
+                *widget_id,
+                EventPhase::Target,
+                &WidgetEvent::Leave,
+            );
+        }
+
+        for widget_id in new_chain.iter().rev().filter(|id| !old_chain.contains(id)) {
+            self.set_property(*widget_id, PROPERTY_HOVERED, PropertyValue::Bool(true));
+            self.deliver(
+                *widget_id,
+                *widget_id,
+                EventPhase::Target,
+                &WidgetEvent::Enter,
+            );
+        }
+
+        self.history = history;
+    }
+
+    /// Retrieves the ID of the innermost `Widget` under the pointer, or `None` if the pointer is
+    /// outside of the window, or has not moved over it yet.
+    pub fn hovered_id(&self) -> Option<u32> {
+        self.hovered_id
+    }
+
+    /// Retrieves the IDs of the ancestors of a `Widget`, from its parent up to the root `Widget`.
+    fn ancestors_of(&self, widget_id: u32) -> Vec<u32> {
+        let mut ancestors = Vec::new();
+        let mut widget_id = widget_id;
+
+        while widget_id != 0 {
+            widget_id = self.get_parent_of(widget_id);
+            ancestors.push(widget_id);
+        }
+
+        ancestors
+    }
+
+    /// Dispatches a `WidgetEvent` to the `Widget` with the given ID, returning `true` if a
+    /// `Widget` consumed it.  The event is first passed to each of the target's ancestors, from
+    /// the root `Widget` down, in the `EventPhase::Capture` phase, then to the target, and then
+    /// back up through the ancestors in the `EventPhase::Bubble` phase.  Dispatch stops as soon as
+    /// a `Widget` consumes the event.
+    pub fn dispatch_to(&mut self, target_id: u32, event: &WidgetEvent) -> bool {
+        let ancestors = self.ancestors_of(target_id);
+        let route = ancestors
+            .iter()
+            .rev()
+            .map(|widget_id| (*widget_id, EventPhase::Capture))
+            .chain(std::iter::once((target_id, EventPhase::Target)))
+            .chain(
+                ancestors
+                    .iter()
+                    .map(|widget_id| (*widget_id, EventPhase::Bubble)),
+            );
+
+        for (widget_id, phase) in route {
+            if self.deliver(widget_id, target_id, phase, event) {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Passes a `WidgetEvent` to a single `Widget`, then acts on any request it made to capture
+    /// or release the pointer.  Returns `true` if the `Widget` consumed the event.
+    fn deliver(
+        &mut self,
+        widget_id: u32,
+        target_id: u32,
+        phase: EventPhase,
+        event: &WidgetEvent,
+    ) -> bool {
+        let mut ctx = EventContext {
+            widget_id,
+            target_id,
+            phase,
+            capture: None,
+        };
+        let consumed = self.cache[widget_id as usize]
+            .widget
+            .borrow_mut()
+            .handle_event(event, &mut ctx);
+
+        match ctx.capture {
+            Some(true) => self.capture_pointer(widget_id),
+            Some(false) if self.captured_id == Some(widget_id) => self.release_pointer(),
+            _ => {}
+        }
+
+        consumed
+    }
+
+    /// Retrieves the ID of the `Widget` that has the keyboard focus, or `None` if no `Widget` has
+    /// the focus.  Keyboard and text input events are sent to the root `Widget` while no other
+    /// `Widget` has the focus.
+    pub fn focused_id(&self) -> Option<u32> {
+        self.focused_id
+            .filter(|widget_id| !self.cache[*widget_id as usize].detached)
+    }
+
+    /// Moves the keyboard focus to the `Widget` with the given ID, returning `true` if it has the
+    /// focus afterward.  A `Widget` can only be focused if it has `PROPERTY_FOCUSABLE` set, and it
+    /// and its ancestors are neither hidden nor disabled.
+    pub fn focus(&mut self, widget_id: u32) -> bool {
+        if !self.is_focusable(widget_id) {
+            return false;
+        }
+
+        self.set_focused(Some(widget_id));
+        true
+    }
+
+    /// Removes the keyboard focus from the focused `Widget`, if any.
+    pub fn blur(&mut self) {
+        self.set_focused(None);
+    }
+
+    /// Moves the keyboard focus to the next `Widget` in the tab order, wrapping around to the
+    /// first, as when the Tab key is pressed.  Returns the ID of the newly focused `Widget`, or
+    /// `None` if no `Widget` can be focused.
+    pub fn focus_next(&mut self) -> Option<u32> {
+        self.focus_step(true)
+    }
+
+    /// Moves the keyboard focus to the previous `Widget` in the tab order, wrapping around to the
+    /// last, as when Shift-Tab is pressed.  Returns the ID of the newly focused `Widget`, or
+    /// `None` if no `Widget` can be focused.
+    pub fn focus_previous(&mut self) -> Option<u32> {
+        self.focus_step(false)
+    }
+
+    /// Retrieves the IDs of the `Widget`s that Tab moves the focus between, in order.  `Widget`s
+    /// with a positive `PROPERTY_TAB_INDEX` come first, in increasing order of their index,
+    /// followed by those with an index of `0`, in the order they appear in the tree.  `Widget`s
+    /// with a negative index can only be focused by a click, or through `focus`.
+    pub fn tab_order(&self) -> Vec<u32> {
+        let mut pending = vec![0];
+        let mut order = Vec::new();
+
+        while let Some(widget_id) = pending.pop() {
+            if self.is_focusable(widget_id) {
+                let tab_index = self.cache[widget_id as usize]
                     .widget
                     .borrow_mut()
                     .properties()
-                    .get_bounds();
-
-                if !is_hidden {
-                    if x >= widget_xy.0
-                        && x <= widget_xy.0 + widget_wh.0
-                        && y >= widget_xy.1
-                        && y <= widget_xy.1 + widget_wh.1
-                    {
-                        if !hidden_widgets.contains(id) {
-                            found_id = *id;
-                        }
-                    }
-                } else {
-                    hidden_widgets.push(*id);
+                    .get_value(PROPERTY_TAB_INDEX);
 
-                    for hidden_widget_id in self.get_children_of(*id) {
-                        hidden_widgets.push(hidden_widget_id);
-                    }
+                if tab_index >= 0 {
+                    order.push((tab_index, widget_id));
+                }
+            }
+
+            pending.extend(self.get_children_of(widget_id).into_iter().rev());
+        }
+
+        // The sort is stable, so `Widget`s with the same index stay in the order of the tree.
+        order.sort_by_key(|(tab_index, _)| if *tab_index > 0 { *tab_index } else { i32::MAX });
+        order.into_iter().map(|(_, widget_id)| widget_id).collect()
+    }
+
+    /// Moves the keyboard focus one step forward or backward through the tab order.
+    fn focus_step(&mut self, forward: bool) -> Option<u32> {
+        let order = self.tab_order();
+
+        if order.is_empty() {
+            return None;
+        }
+
+        let position = self
+            .focused_id()
+            .and_then(|focused_id| order.iter().position(|widget_id| *widget_id == focused_id));
+        let next = match (position, forward) {
+            (Some(position), true) => (position + 1) % order.len(),
+            (Some(position), false) => (position + order.len() - 1) % order.len(),
+            (None, true) => 0,
+            (None, false) => order.len() - 1,
+        };
+
+        self.set_focused(Some(order[next]));
+        Some(order[next])
+    }
+
+    /// Determines whether or not the `Widget` with the given ID can receive the keyboard focus.
+    fn is_focusable(&self, widget_id: u32) -> bool {
+        let flag = |widget_id: u32, property_key: u32| {
+            self.cache[widget_id as usize]
+                .widget
+                .borrow_mut()
+                .properties()
+                .get_bool(property_key)
+        };
+
+        !self.cache[widget_id as usize].detached
+            && flag(widget_id, PROPERTY_FOCUSABLE)
+            && std::iter::once(widget_id)
+                .chain(self.ancestors_of(widget_id))
+                .all(|widget_id| {
+                    !flag(widget_id, PROPERTY_HIDDEN) && !flag(widget_id, PROPERTY_DISABLED)
+                })
+    }
+
+    /// Moves the keyboard focus to the `Widget` with the given ID, or removes it if `None`.  The
+    /// previously focused `Widget` is sent a `WidgetEvent::FocusLost`, and has `PROPERTY_FOCUSED`
+    /// cleared, then the newly focused `Widget` has `PROPERTY_FOCUSED` set, and is sent a
+    /// `WidgetEvent::FocusGained`.  These events are not passed on to the ancestors of the
+    /// `Widget`, and the changes are not recorded in the history.
+    fn set_focused(&mut self, focused_id: Option<u32>) {
+        let old_focused_id = self.focused_id();
+
+        if focused_id == old_focused_id {
+            return;
+        }
+
+        let history = self.history.take();
+
+        self.focused_id = focused_id;
+
+        if let Some(widget_id) = old_focused_id {
+            self.delete_property(widget_id, PROPERTY_FOCUSED);
+            self.deliver(
+                widget_id,
+                widget_id,
+                EventPhase::Target,
+                &WidgetEvent::FocusLost,
+            );
+        }
+
+        if let Some(widget_id) = focused_id {
+            self.set_property(widget_id, PROPERTY_FOCUSED, PropertyValue::Bool(true));
+            self.deliver(
+                widget_id,
+                widget_id,
+                EventPhase::Target,
+                &WidgetEvent::FocusGained,
+            );
+        }
+
+        self.history = history;
+    }
+
+    /// Walks the children of a `Widget` to find the topmost visible `Widget` containing the point,
+    /// where `clip` is the visible area of the `Widget`.
+    fn find_id_at_point(&self, widget_id: u32, point: Point, clip: Option<Rect>) -> Option<u32> {
+        let mut found_id = None;
+
+        for id in self.get_children_of(widget_id) {
+            let is_hidden = self.cache[id as usize]
+                .widget
+                .borrow_mut()
+                .properties()
+                .get_bool(PROPERTY_HIDDEN);
+
+            if is_hidden {
+                continue;
+            }
+
+            if let Some(visible_rect) = self.visible_rect(id, clip) {
+                if visible_rect.contains_point(point) {
+                    found_id = Some(id);
+                }
+
+                if let Some(child_id) = self.find_id_at_point(id, point, Some(visible_rect)) {
+                    found_id = Some(child_id);
                 }
             }
         }
@@ -135,20 +639,41 @@ impl WidgetCache {
         found_id
     }
 
+    /// Retrieves the bounding `Rect` of a `Widget`, or `None` if it has no area.
+    fn widget_rect(&self, widget_id: u32) -> Option<Rect> {
+        self.cache[widget_id as usize]
+            .widget
+            .borrow_mut()
+            .properties()
+            .border_rect()
+    }
+
+    /// Retrieves the portion of a `Widget`'s bounds that falls within the `clip` area, or `None` if
+    /// no portion of it is visible.
+    fn visible_rect(&self, widget_id: u32, clip: Option<Rect>) -> Option<Rect> {
+        self.widget_rect(widget_id)?.intersection(clip?)
+    }
+
     /// Retrieves the `Widget` stored by its `RefCell<Box>` reference.
     #[inline]
     pub fn get(&self, widget_id: u32) -> &RefCell<Box<dyn Widget>> {
         &self.cache[widget_id as usize].widget
     }
 
+    /// Determines whether or not the `Widget` with the given ID has been detached from the tree,
+    /// because its addition, or the addition of one of its ancestors, has been undone.
+    pub fn is_detached(&self, widget_id: u32) -> bool {
+        self.cache[widget_id as usize].detached
+    }
+
     /// Retrieves the ID of a `Widget` by its `name`.  If the `name` could not be located, the top
-    /// level ID `0` is returned.
+    /// level ID `0` is returned.  `Widget`s whose addition has been undone are not located.
     #[inline]
     pub fn get_by_name(&self, name: String) -> u32 {
         let cache_size = self.size();
 
         for i in 0..cache_size {
-            if self.cache[i as usize].name == name {
+            if self.cache[i as usize].name == name && !self.cache[i as usize].detached {
                 return i;
             }
         }
@@ -156,6 +681,12 @@ impl WidgetCache {
         0
     }
 
+    /// Retrieves the name of the widget ID specified.
+    #[inline]
+    pub fn get_name_of(&self, widget_id: u32) -> &str {
+        &self.cache[widget_id as usize].name
+    }
+
     /// Retrieves the parent ID of the widget ID specified.  If the widget is a top level widget (meaning
     /// there are no additional parents), a 0 will be returned.
     #[inline]
@@ -182,7 +713,7 @@ impl WidgetCache {
     pub fn add(&mut self, mut widget: Box<dyn Widget>, widget_name: String, parent_id: u32) -> u32 {
         // use get_by_name to make sure the widget doesn't already exist by name.  If it does,
         // throw an error.
-        let existent_id = WidgetCache::get_by_name(&self, widget_name.to_string());
+        let existent_id = WidgetCache::get_by_name(self, widget_name.to_string());
         if existent_id != 0 {
             // should we really throw an error?
             panic!("Widget {} was already added!", existent_id)
@@ -194,12 +725,672 @@ impl WidgetCache {
         self.cache
             .push(WidgetCacheContainer::new(widget, widget_name, parent_id));
 
-        let widget_id: u32 = self.size() as u32;
+        let widget_id: u32 = self.size() - 1;
         self.cache[parent_id as usize].children.push(widget_id);
+        self.cache[parent_id as usize].needs_layout = true;
+        self.apply_theme(widget_id);
+        self.inherit(widget_id);
+        self.layout_widget(widget_id);
+
+        if let Some(history) = self.history.as_mut() {
+            history.record(vec![HistoryEntry::Added {
+                widget_id,
+                parent_id,
+                position: self.cache[parent_id as usize].children.len() - 1,
+            }]);
+        }
 
         widget_id
     }
 
+    /// Replaces the `Theme` used by the cache, and re-applies it to all of the `Widget`s in the
+    /// cache.  Any `Widget`s whose properties are affected by the change are invalidated.
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+
+        for widget_id in 0..self.size() {
+            self.apply_theme(widget_id);
+        }
+
+        self.propagate_inheritance(0, true);
+
+        // Parents are always added before their children, so they are placed first.
+        for widget_id in 1..self.size() {
+            self.layout_widget(widget_id);
+        }
+
+        for container in self.cache.iter_mut() {
+            container.needs_layout = true;
+        }
+    }
+
+    /// Applies the current `Theme` to the `Widget` with the given ID, invalidating it if any of the
+    /// properties supplied by the `Theme` have changed.  Returns `true` if the `Widget` changed.
+    fn apply_theme(&mut self, widget_id: u32) -> bool {
+        let container = &self.cache[widget_id as usize];
+        let mut widget = container.widget.borrow_mut();
+        let widget_type = widget.widget_type();
+        let theme_properties =
+            self.theme
+                .resolve(widget_type, &container.name, widget.properties());
+
+        if widget.properties().set_theme_properties(theme_properties) {
+            widget.invalidate();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Declares whether or not a property is inheritable.  When an inheritable property is not set
+    /// on a `Widget`, or by the `Theme`, the value of its parent is used instead.  By default, the
+    /// font name, font size and text color are inheritable.  Colors and the hidden state can be
+    /// made inheritable, so that setting them on a container cascades to its children.  Opacity
+    /// always applies to children, as it is combined with the opacity of the parent when drawn.
+    pub fn set_inheritable(&mut self, property_key: u32, inheritable: bool) {
+        if inheritable {
+            self.inheritable.insert(property_key);
+        } else {
+            self.inheritable.remove(&property_key);
+        }
+
+        self.propagate_inheritance(0, true);
+    }
+
+    /// Recomputes the inheritable properties of the `Widget` with the given ID from its parent,
+    /// invalidating it if any of the inherited values changed.  Returns `true` if the `Widget`
+    /// changed.
+    fn inherit(&mut self, widget_id: u32) -> bool {
+        if widget_id == 0 {
+            return false;
+        }
+
+        let parent_id = self.cache[widget_id as usize].parent;
+        let inherited: HashMap<u32, PropertyValue> = {
+            let mut parent = self.cache[parent_id as usize].widget.borrow_mut();
+
+            self.inheritable
+                .iter()
+                .filter_map(|property_key| {
+                    parent
+                        .properties()
+                        .get(*property_key)
+                        .map(|property_value| (*property_key, property_value.clone()))
+                })
+                .collect()
+        };
+        let mut widget = self.cache[widget_id as usize].widget.borrow_mut();
+
+        if widget.properties().set_inherited_properties(inherited) {
+            widget.invalidate();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Recomputes the inheritable properties of all descendants of the `Widget` with the given ID.
+    /// Unless `full` is set, descendants of a `Widget` whose inherited values did not change are
+    /// not visited.
+    fn propagate_inheritance(&mut self, widget_id: u32, full: bool) {
+        for child_id in self.get_children_of(widget_id) {
+            if self.inherit(child_id) || full {
+                self.propagate_inheritance(child_id, full);
+            }
+        }
+    }
+
+    /// Sets a property for the `Widget` with the given ID through `Widget::set_property`, so that
+    /// the `Widget` can respond to the change.  If the value changed, all subscribers observing
+    /// the `Widget` are notified of the change.  Properties set directly through a `Widget`'s
+    /// `properties()` are not observed.
+    pub fn set_property(
+        &mut self,
+        widget_id: u32,
+        property_key: u32,
+        property_value: PropertyValue,
+    ) {
+        let old_value = self.cache[widget_id as usize]
+            .widget
+            .borrow_mut()
+            .properties()
+            .get_local(property_key)
+            .cloned();
+
+        if old_value.as_ref() == Some(&property_value) {
+            return;
+        }
+
+        self.cache[widget_id as usize]
+            .widget
+            .borrow_mut()
+            .set_property(property_key, property_value.clone());
+
+        self.properties_changed(vec![PropertyChange {
+            widget_id,
+            property_key,
+            old_value,
+            new_value: Some(property_value),
+        }]);
+    }
+
+    /// Deletes a property for the `Widget` with the given ID through `Widget::delete_property`.  If
+    /// the property was set, all subscribers observing the `Widget` are notified of the change.
+    pub fn delete_property(&mut self, widget_id: u32, property_key: u32) {
+        let old_value = self.cache[widget_id as usize]
+            .widget
+            .borrow_mut()
+            .properties()
+            .get_local(property_key)
+            .cloned();
+
+        if old_value.is_none() {
+            return;
+        }
+
+        self.cache[widget_id as usize]
+            .widget
+            .borrow_mut()
+            .delete_property(property_key);
+
+        self.properties_changed(vec![PropertyChange {
+            widget_id,
+            property_key,
+            old_value,
+            new_value: None,
+        }]);
+    }
+
+    /// Applies a group of property changes to the `Widget` with the given ID at once.  The
+    /// `updater` is given a `PropertyTransaction` to record the changes in, which are then applied
+    /// together, invalidating the `Widget` once if any change affects its drawing.  The `Widget`'s
+    /// `on_property_changed` hook is called, and subscribers are notified, only for properties
+    /// whose values actually changed, which are also returned.
+    pub fn update<F>(&mut self, widget_id: u32, updater: F) -> Vec<PropertyChange>
+    where
+        F: FnOnce(&mut PropertyTransaction),
+    {
+        let mut transaction = PropertyTransaction::default();
+
+        updater(&mut transaction);
+
+        if transaction.is_empty() {
+            return Vec::new();
+        }
+
+        let changes: Vec<PropertyChange> = {
+            let mut widget = self.cache[widget_id as usize].widget.borrow_mut();
+            let changed: Vec<ChangedProperty> = widget.properties().apply(transaction);
+
+            for change in &changed {
+                widget.on_property_changed(
+                    change.property_key,
+                    change.old_value.as_ref(),
+                    change.new_value.as_ref(),
+                );
+            }
+
+            changed
+                .into_iter()
+                .map(|change| PropertyChange {
+                    widget_id,
+                    property_key: change.property_key,
+                    old_value: change.old_value,
+                    new_value: change.new_value,
+                })
+                .collect()
+        };
+
+        self.properties_changed(changes.clone());
+
+        changes
+    }
+
+    /// Resizes the root `Widget`, such as when the window is resized.  `Widget`s placed relative to
+    /// the root are placed again.  The change is not recorded in the history.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        let history = self.history.take();
+
+        self.set_property(0, PROPERTY_SIZE, PropertyValue::Pair(width, height));
+        self.history = history;
+    }
+
+    /// Places the `Widget` with the given ID within the content area of its parent, if it has a
+    /// `Placement`.  The resolved origin and size are set through `update`, so the `Widget`'s own
+    /// children are placed again if they change.  Resolved values are not recorded in the history.
+    fn layout_widget(&mut self, widget_id: u32) {
+        if widget_id == 0 {
+            return;
+        }
+
+        let parent_id = self.cache[widget_id as usize].parent;
+        let area = match self.cache[parent_id as usize]
+            .widget
+            .borrow_mut()
+            .properties()
+            .content_rect()
+        {
+            Some(area) => area,
+            None => return,
+        };
+        let (origin, size) = {
+            let mut widget = self.cache[widget_id as usize].widget.borrow_mut();
+            let properties = widget.properties();
+            let placement = match Placement::from_properties(properties) {
+                Some(placement) => placement,
+                None => return,
+            };
+
+            placement.resolve(
+                area,
+                properties.get_margin(),
+                properties.get_origin(),
+                properties.get_bounds(),
+            )
+        };
+        let history = self.history.take();
+
+        self.update(widget_id, |transaction| {
+            transaction
+                .set_origin(origin.0, origin.1)
+                .set_bounds(size.0, size.1);
+        });
+        self.history = history;
+    }
+
+    /// Runs the layout pass, in which container `Widget`s arrange their children through
+    /// `Widget::arrange`.  Only containers that need it are arranged: those whose bounds, content
+    /// area or layout properties have changed, or that have had a child added, hidden, shown, or
+    /// changed in size.  This is called by `draw`, so it only needs to be called directly when the
+    /// arranged bounds are needed before the next draw, such as for hit testing.
+    pub fn layout(&mut self) {
+        // Parents are always added before their children, so a container is arranged before any
+        // containers within it, whose bounds it may change.
+        for widget_id in 0..self.size() {
+            if self.cache[widget_id as usize].needs_layout
+                && !self.cache[widget_id as usize].detached
+            {
+                self.arrange(widget_id);
+            }
+
+            self.cache[widget_id as usize].needs_layout = false;
+        }
+    }
+
+    /// Measures the size the `Widget` with the given ID needs, within the `available` size.
+    /// Containers are measured from the sizes their visible children need, through
+    /// `Widget::measure_children`; other `Widget`s are measured through `Widget::measure`.  This
+    /// can be used to size a window to fit its content.
+    pub fn measure(&mut self, widget_id: u32, available: Size) -> Size {
+        let insets = self.cache[widget_id as usize]
+            .widget
+            .borrow_mut()
+            .properties()
+            .get_insets();
+        let content = (
+            (available.0 as i32 - insets.left - insets.right).max(0) as u32,
+            (available.1 as i32 - insets.top - insets.bottom).max(0) as u32,
+        );
+        let items: Vec<LayoutItem> = self
+            .visible_children_of(widget_id)
+            .into_iter()
+            .map(|child_id| self.layout_item(child_id, content))
+            .collect();
+        let mut widget = self.cache[widget_id as usize].widget.borrow_mut();
+
+        widget
+            .measure_children(available, &items)
+            .unwrap_or_else(|| widget.measure(available))
+    }
+
+    /// Describes the space the `Widget` with the given ID asks its container for, measured within
+    /// the `available` size of the container's content area.
+    fn layout_item(&mut self, widget_id: u32, available: Size) -> LayoutItem {
+        let preferred = self.measure(widget_id, available);
+        let mut widget = self.cache[widget_id as usize].widget.borrow_mut();
+        let mut item = LayoutItem::from_properties(widget.properties());
+
+        item.preferred = preferred;
+        item.min = widget.min_size();
+        item.max = widget.max_size();
+        item
+    }
+
+    /// Retrieves the IDs of the children of a `Widget` that are not hidden.
+    fn visible_children_of(&self, widget_id: u32) -> Vec<u32> {
+        self.get_children_of(widget_id)
+            .into_iter()
+            .filter(|child_id| {
+                !self.cache[*child_id as usize]
+                    .widget
+                    .borrow_mut()
+                    .properties()
+                    .get_bool(PROPERTY_HIDDEN)
+            })
+            .collect()
+    }
+
+    /// Arranges the visible children of a container `Widget`, setting the bounds returned by its
+    /// `Widget::arrange` through `update`.  Arranged bounds are not recorded in the history.
+    fn arrange(&mut self, widget_id: u32) {
+        let children = self.visible_children_of(widget_id);
+        let area = match self.cache[widget_id as usize]
+            .widget
+            .borrow_mut()
+            .properties()
+            .content_rect()
+        {
+            Some(area) => area,
+            None => return,
+        };
+        let items: Vec<LayoutItem> = children
+            .iter()
+            .map(|child_id| self.layout_item(*child_id, (area.width(), area.height())))
+            .collect();
+        let rects = {
+            let mut widget = self.cache[widget_id as usize].widget.borrow_mut();
+
+            match widget.arrange(area, &items) {
+                Some(rects) => rects,
+                None => return,
+            }
+        };
+        let history = self.history.take();
+
+        for (child_id, rect) in children.into_iter().zip(rects) {
+            self.update(child_id, |transaction| {
+                transaction
+                    .set_origin(rect.x(), rect.y())
+                    .set_bounds(rect.width(), rect.height());
+            });
+        }
+
+        self.history = history;
+    }
+
+    /// Places the children of the `Widget` with the given ID, after its bounds have changed.
+    fn layout_children(&mut self, widget_id: u32) {
+        for child_id in self.get_children_of(widget_id) {
+            self.layout_widget(child_id);
+        }
+    }
+
+    /// Responds to changes to the properties of a single `Widget`: the `Theme` is re-applied if
+    /// any of its selectors depend on a changed property, inheritable properties are propagated to
+    /// the `Widget`'s descendants, the `Widget` and its children are placed again if their
+    /// placement or the `Widget`'s bounds changed, and subscribers are notified.
+    fn properties_changed(&mut self, changes: Vec<PropertyChange>) {
+        let widget_id = match changes.first() {
+            Some(change) => change.widget_id,
+            None => return,
+        };
+        let themed = changes
+            .iter()
+            .any(|change| self.theme.uses_state(change.property_key))
+            && self.apply_theme(widget_id);
+
+        if themed
+            || changes
+                .iter()
+                .any(|change| self.inheritable.contains(&change.property_key))
+        {
+            self.propagate_inheritance(widget_id, false);
+        }
+
+        if changes.iter().any(|change| {
+            matches!(
+                change.property_key,
+                PROPERTY_RELATIVE_SIZE
+                    | PROPERTY_RELATIVE_ORIGIN
+                    | PROPERTY_ANCHORS
+                    | PROPERTY_MARGIN
+            )
+        }) {
+            self.layout_widget(widget_id);
+        }
+
+        if changes.iter().any(|change| {
+            matches!(
+                change.property_key,
+                PROPERTY_ORIGIN
+                    | PROPERTY_BORDER_WIDTH
+                    | PROPERTY_BORDER_WIDTHS
+                    | PROPERTY_BORDER_STYLE
+            ) || invalidation_of(change.property_key) == Invalidation::Relayout
+        }) {
+            self.layout_children(widget_id);
+            self.cache[widget_id as usize].needs_layout = true;
+        }
+
+        if changes.iter().any(|change| {
+            change.property_key == PROPERTY_HIDDEN
+                || invalidation_of(change.property_key) == Invalidation::Relayout
+        }) {
+            let parent_id = self.cache[widget_id as usize].parent;
+
+            self.cache[parent_id as usize].needs_layout = true;
+        }
+
+        for change in &changes {
+            self.notify(change);
+        }
+
+        if let Some(history) = self.history.as_mut() {
+            history.record(changes.into_iter().map(HistoryEntry::Property).collect());
+        }
+    }
+
+    /// Starts recording changes made through `add`, `set_property`, `delete_property` and `update`,
+    /// so that they can be undone.  Changes made by animations are not recorded.  If history is
+    /// already being recorded, this has no effect.
+    pub fn enable_history(&mut self) {
+        if self.history.is_none() {
+            self.history = Some(History::default());
+        }
+    }
+
+    /// Stops recording changes, discarding all recorded steps.
+    pub fn disable_history(&mut self) {
+        self.history = None;
+    }
+
+    /// Starts a step in the history, so that all changes made until `end_step` is called are undone
+    /// and redone together.  Outside of a step, each call that changes the cache is a step of its
+    /// own.
+    pub fn begin_step(&mut self) {
+        if let Some(history) = self.history.as_mut() {
+            history.begin_step();
+        }
+    }
+
+    /// Ends a step in the history started by `begin_step`.
+    pub fn end_step(&mut self) {
+        if let Some(history) = self.history.as_mut() {
+            history.end_step();
+        }
+    }
+
+    /// Returns a flag indicating whether or not there is a recorded step that can be undone.
+    pub fn can_undo(&self) -> bool {
+        self.history.as_ref().is_some_and(History::can_undo)
+    }
+
+    /// Returns a flag indicating whether or not there is an undone step that can be redone.
+    pub fn can_redo(&self) -> bool {
+        self.history.as_ref().is_some_and(History::can_redo)
+    }
+
+    /// Undoes the most recent step in the history, restoring the previous values of changed
+    /// properties, and removing added `Widget`s from their parents.  Affected `Widget`s are
+    /// invalidated, and subscribers are notified of the restored values.  Returns `false` if there
+    /// was nothing to undo.
+    ///
+    /// Removed `Widget`s are only detached, so that their IDs stay valid, and they can be redone.
+    /// Their shadows are destroyed, but the `Widget`s and their textures are retained by the cache,
+    /// even once the step can no longer be redone.
+    pub fn undo(&mut self) -> bool {
+        let mut history = match self.history.take() {
+            Some(history) => history,
+            None => return false,
+        };
+        let step = history.take_undo();
+
+        if let Some(entries) = &step {
+            for entry in entries.iter().rev() {
+                self.apply_history_entry(entry, true);
+            }
+        }
+
+        self.history = Some(history);
+        step.is_some()
+    }
+
+    /// Redoes the most recently undone step in the history.  Returns `false` if there was nothing
+    /// to redo.
+    pub fn redo(&mut self) -> bool {
+        let mut history = match self.history.take() {
+            Some(history) => history,
+            None => return false,
+        };
+        let step = history.take_redo();
+
+        if let Some(entries) = &step {
+            for entry in entries {
+                self.apply_history_entry(entry, false);
+            }
+        }
+
+        self.history = Some(history);
+        step.is_some()
+    }
+
+    /// Reverts (when `undo` is set) or re-applies a single recorded change.
+    fn apply_history_entry(&mut self, entry: &HistoryEntry, undo: bool) {
+        match entry {
+            HistoryEntry::Property(change) => {
+                let value = if undo {
+                    change.old_value.clone()
+                } else {
+                    change.new_value.clone()
+                };
+                let property_key = change.property_key;
+
+                self.update(change.widget_id, |transaction| {
+                    match value {
+                        Some(property_value) => transaction.set(property_key, property_value),
+                        None => transaction.delete(property_key),
+                    };
+                });
+            }
+            HistoryEntry::Added {
+                widget_id,
+                parent_id,
+                position,
+            } => {
+                let subtree = self.subtree_of(*widget_id);
+
+                if undo {
+                    self.release_subtree(&subtree);
+                }
+
+                let children = &mut self.cache[*parent_id as usize].children;
+
+                if undo {
+                    children.retain(|child_id| child_id != widget_id);
+                } else {
+                    children.insert((*position).min(children.len()), *widget_id);
+                }
+
+                for subtree_id in subtree {
+                    let container = &mut self.cache[subtree_id as usize];
+
+                    container.detached = undo;
+
+                    if undo {
+                        container.clear_shadow();
+                    }
+                }
+
+                self.cache[*parent_id as usize].needs_layout = true;
+                self.cache[*parent_id as usize]
+                    .widget
+                    .borrow_mut()
+                    .invalidate();
+                self.cache[*widget_id as usize]
+                    .widget
+                    .borrow_mut()
+                    .invalidate();
+            }
+        }
+    }
+
+    /// Retrieves the IDs of a `Widget` and all of its descendants, the `Widget` first.
+    fn subtree_of(&self, widget_id: u32) -> Vec<u32> {
+        let mut subtree = vec![widget_id];
+        let mut index = 0;
+
+        while index < subtree.len() {
+            subtree.extend_from_slice(&self.cache[subtree[index] as usize].children);
+            index += 1;
+        }
+
+        subtree
+    }
+
+    /// Moves the pointer, pointer capture and keyboard focus away from a subtree of `Widget`s that
+    /// is about to be detached, so that no state refers to a `Widget` that cannot be reached.  A
+    /// press or drag in progress is abandoned without any further events.
+    fn release_subtree(&mut self, subtree: &[u32]) {
+        let within = |widget_id: Option<u32>| widget_id.is_some_and(|id| subtree.contains(&id));
+
+        if within(self.captured_id) {
+            self.captured_id = None;
+            self.drag = None;
+            self.pressed = None;
+        }
+
+        if within(self.hovered_id) {
+            self.set_hovered(None);
+        }
+
+        if within(self.focused_id()) {
+            self.set_focused(None);
+        }
+    }
+
+    /// Registers a callback that is called whenever a property is changed through `set_property`,
+    /// `delete_property` or `update`.  If a `widget_id` is given, only changes to that `Widget` are
+    /// reported, otherwise, changes to all `Widget`s are reported.  Returns the ID of the
+    /// subscription, which can be used to `unsubscribe` the callback.
+    pub fn subscribe(&mut self, widget_id: Option<u32>, callback: PropertySubscriber) -> u32 {
+        let subscription_id = self.next_subscription_id;
+
+        self.next_subscription_id += 1;
+        self.subscriptions.push(PropertySubscription {
+            subscription_id,
+            widget_id,
+            callback,
+        });
+
+        subscription_id
+    }
+
+    /// Removes a callback registered by `subscribe`.
+    pub fn unsubscribe(&mut self, subscription_id: u32) {
+        self.subscriptions
+            .retain(|subscription| subscription.subscription_id != subscription_id);
+    }
+
+    /// Sends a property change to all subscribers observing the changed `Widget`.
+    fn notify(&mut self, change: &PropertyChange) {
+        for subscription in &mut self.subscriptions {
+            if subscription.widget_id.is_none() || subscription.widget_id == Some(change.widget_id)
+            {
+                (subscription.callback)(change);
+            }
+        }
+    }
+
     /// Retrieves the total number of `Widget`s in the cache.
     #[inline]
     pub fn size(&self) -> u32 {
@@ -221,6 +1412,74 @@ impl WidgetCache {
         invalidated
     }
 
+    /// Starts running an `Animation`, which is advanced each time `tick` is called.  Returns the
+    /// ID of the animation, which can be used to stop it with `stop_animation`.
+    pub fn animate(&mut self, animation: Animation) -> u32 {
+        let animation_id = self.next_animation_id;
+
+        self.next_animation_id += 1;
+        self.animations.push((animation_id, animation));
+
+        animation_id
+    }
+
+    /// Stops a running animation, leaving the animated property at its current value.  The
+    /// animation's completion callback is not called.
+    pub fn stop_animation(&mut self, animation_id: u32) {
+        self.animations.retain(|(id, _)| *id != animation_id);
+    }
+
+    /// Returns a flag indicating whether or not any animations are running.
+    pub fn animating(&self) -> bool {
+        !self.animations.is_empty()
+    }
+
+    /// Advances all running animations by the `elapsed` time since the last call.  Animated
+    /// properties are changed through `update`, so the `Widget`s are invalidated and subscribers
+    /// are notified as with any other property change.  Completed animations set their final
+    /// value, call their completion callback, and are removed.  Animations of `Widget`s that have
+    /// been detached by undoing their addition are stopped, as with `stop_animation`.  As time is
+    /// only ever advanced by this call, animations can be driven by any clock (or a fixed step,
+    /// when testing.)
+    pub fn tick(&mut self, elapsed: Duration) {
+        let mut animations = std::mem::take(&mut self.animations);
+        let history = self.history.take();
+
+        animations.retain_mut(|(_, animation)| {
+            if self.is_detached(animation.widget_id) {
+                return false;
+            }
+
+            let widget = &self.cache[animation.widget_id as usize].widget;
+            let property_key = animation.property_key;
+            let (value, complete) = match animation.step(elapsed, || {
+                widget
+                    .borrow_mut()
+                    .properties()
+                    .get_or_default(property_key)
+            }) {
+                Some(step) => step,
+                None => return true,
+            };
+
+            self.update(animation.widget_id, |transaction| {
+                transaction.set(property_key, value);
+            });
+
+            if complete {
+                if let Some(on_complete) = animation.on_complete.as_mut() {
+                    on_complete(animation.widget_id);
+                }
+            }
+
+            !complete
+        });
+
+        animations.append(&mut self.animations);
+        self.animations = animations;
+        self.history = history;
+    }
+
     /// Recursive drawing function that takes a `Widget`'s ID, and gets a list of the children that
     /// are owned for that `Widget`.  It then walks the tree of all of the children, and draws the
     /// contents into a `Texture`.  The `TextureCache` is sent such that the `Widget` has the ability
@@ -232,67 +1491,173 @@ impl WidgetCache {
     /// copied back to screen in a very quick operation.
     ///
     /// Any `Widget`s that have a property of `PROPERTY_HIDDEN` set will short circuit the draw
-    /// for that `Widget` and its children.
+    /// for that `Widget` and its children.  Each `Widget` is clipped to the visible area of its
+    /// parent, so `Widget`s positioned partially outside of their parent (or the window) are only
+    /// drawn where they are visible, and `Widget`s that are not visible at all are skipped.
+    /// A `Widget` with a `PROPERTY_SHADOW_COLOR` has its shadow drawn beneath it, which may extend
+    /// beyond the `Widget`'s bounds, but not beyond the visible area of its parent.
     ///
     /// Drawing is computed off-screen in GPU memory, so this is also a very fast operation, which
     /// should theoretically take place in less than a single draw frame.
     pub fn draw(&mut self, widget_id: u32, c: &mut Canvas<Window>) {
-        let children_of_widget = self.get_children_of(widget_id);
+        self.layout();
 
-        if children_of_widget.is_empty() {
-            return;
-        }
+        let clip = self.widget_rect(widget_id);
+        let opacity = self.opacity_of(widget_id);
 
-        for id in &children_of_widget {
-            // TODO: check this
-            let new_id: usize = *id as usize - 1;
-            if self.cache.len() > new_id as usize {
-                let paint_widget = &mut self.cache[new_id];
-                let is_hidden = paint_widget
-                    .widget
-                    .borrow_mut()
-                    .properties()
-                    .get_bool(PROPERTY_HIDDEN);
-                let widget_xy = paint_widget.widget.borrow_mut().properties().get_origin();
-                let widget_wh = paint_widget.widget.borrow_mut().properties().get_bounds();
-
-                if !is_hidden {
-                    match paint_widget
-                        .widget
-                        .borrow_mut()
-                        .draw(c, &mut self.texture_cache)
-                    {
-                        Some(texture) => {
-                            c.copy(
-                                texture,
-                                None,
-                                Rect::new(
-                                    widget_xy.0 as i32,
-                                    widget_xy.1 as i32,
-                                    widget_wh.0,
-                                    widget_wh.1,
-                                ),
-                            )
-                            .unwrap();
-                        }
-                        None => eprintln!("No texture presented: ID={}", new_id),
-                    };
+        self.draw_clipped(widget_id, c, clip, opacity);
+        c.set_clip_rect(None);
+    }
 
-                    paint_widget
-                        .widget
-                        .borrow_mut()
-                        .properties()
-                        .delete(PROPERTY_INVALIDATED);
-                }
+    /// Retrieves the opacity a `Widget` is drawn with: its own `PROPERTY_OPACITY`, combined with
+    /// the opacity of each of its parents.
+    fn opacity_of(&self, widget_id: u32) -> u8 {
+        let parent_opacity = if widget_id == 0 {
+            255
+        } else {
+            self.opacity_of(self.cache[widget_id as usize].parent)
+        };
+
+        combine_opacity(
+            self.cache[widget_id as usize]
+                .widget
+                .borrow_mut()
+                .properties(),
+            parent_opacity,
+        )
+    }
+
+    /// Draws the shadow of a `Widget`, if it has one, clipped to the visible area of its parent.
+    /// The shadow is generated when it is first drawn, and again whenever the size of the `Widget`
+    /// or its shadow properties change, destroying the previous shadow.  The shadow is drawn with
+    /// the opacity of the `Widget`.
+    fn draw_shadow(
+        &mut self,
+        widget_id: u32,
+        c: &mut Canvas<Window>,
+        clip: Option<Rect>,
+        opacity: u8,
+    ) {
+        let widget_rect = match self.widget_rect(widget_id) {
+            Some(widget_rect) => widget_rect,
+            None => return,
+        };
+        let shadow = Shadow::from_properties(
+            self.cache[widget_id as usize]
+                .widget
+                .borrow_mut()
+                .properties(),
+        );
+        let container = &mut self.cache[widget_id as usize];
+        let shadow = match shadow {
+            Some(shadow) => shadow,
+            None => {
+                container.clear_shadow();
+                return;
             }
+        };
+        let size = (widget_rect.width(), widget_rect.height());
+
+        if container.shadow.as_ref().map_or(true, |cached| {
+            cached.shadow != shadow || cached.size != size
+        }) {
+            container.clear_shadow();
+            container.shadow = shadow.pixels(size.0, size.1).map(|(pixels, shadow_size)| {
+                let mut texture = c
+                    .texture_creator()
+                    .create_texture_static(PixelFormatEnum::RGBA32, shadow_size.0, shadow_size.1)
+                    .unwrap();
+
+                texture
+                    .update(None, &pixels, shadow_size.0 as usize * 4)
+                    .unwrap();
+                texture.set_blend_mode(BlendMode::Blend);
+
+                CachedShadow {
+                    shadow,
+                    size,
+                    texture,
+                }
+            });
+        }
+
+        if let (Some(cached), Some(shadow_rect)) =
+            (&mut container.shadow, shadow.bounds(widget_rect))
+        {
+            cached.texture.set_alpha_mod(opacity);
+            c.set_clip_rect(clip);
+            c.copy(&cached.texture, None, shadow_rect).unwrap();
+        }
+    }
 
-            if new_id as u32 != widget_id {
-                self.draw(new_id as u32, c);
+    /// Draws the children of a `Widget`, where `clip` is the visible area of the `Widget`, and
+    /// `opacity` is the opacity it is drawn with.
+    fn draw_clipped(
+        &mut self,
+        widget_id: u32,
+        c: &mut Canvas<Window>,
+        clip: Option<Rect>,
+        opacity: u8,
+    ) {
+        for id in self.get_children_of(widget_id) {
+            let is_hidden = self.cache[id as usize]
+                .widget
+                .borrow_mut()
+                .properties()
+                .get_bool(PROPERTY_HIDDEN);
+
+            if is_hidden {
+                continue;
             }
+
+            let opacity = combine_opacity(
+                self.cache[id as usize].widget.borrow_mut().properties(),
+                opacity,
+            );
+
+            self.draw_shadow(id, c, clip, opacity);
+
+            let visible_rect = match self.visible_rect(id, clip) {
+                Some(visible_rect) => visible_rect,
+                None => continue,
+            };
+            let widget_rect = self.widget_rect(id).unwrap();
+            let paint_widget = &mut self.cache[id as usize];
+
+            match paint_widget
+                .widget
+                .borrow_mut()
+                .draw(c, &mut self.texture_cache)
+            {
+                Some(texture) => {
+                    // The texture is only lent out by the `Widget`, so its alpha modulation is set
+                    // directly.  It is set on every draw, as the `Widget` may reuse the texture.
+                    unsafe { sdl2::sys::SDL_SetTextureAlphaMod(texture.raw(), opacity) };
+                    c.set_clip_rect(visible_rect);
+                    c.copy(texture, None, widget_rect).unwrap();
+                }
+                None => eprintln!("No texture presented: ID={}", id),
+            };
+
+            paint_widget
+                .widget
+                .borrow_mut()
+                .properties()
+                .delete(PROPERTY_INVALIDATED);
+
+            self.draw_clipped(id, c, Some(visible_rect), opacity);
         }
     }
 }
 
+/// Combines the `PROPERTY_OPACITY` of a `Widget`, from `0` (transparent) to `255` (opaque), with
+/// the opacity of its parent.
+fn combine_opacity(properties: &WidgetProperties, parent_opacity: u8) -> u8 {
+    let opacity = properties.get_value(PROPERTY_OPACITY).clamp(0, 255) as u32;
+
+    (opacity * u32::from(parent_opacity) / 255) as u8
+}
+
 /// This is a storage object for the `TextureCache`.
 pub struct TextureCache {
     images: HashMap<String, Texture>,
@@ -317,12 +1682,50 @@ impl TextureCache {
         &self.ttf_context
     }
 
-    /// Returns an image loaded into a `Texture` reference, caching it in memory.
-    pub fn get_image(&mut self, c: &mut Canvas<Window>, image_name: String) -> &Texture {
-        self.images.entry(image_name.clone()).or_insert({
-            c.texture_creator()
-                .load_texture(Path::new(&image_name))
-                .unwrap()
-        })
+    /// Returns an image loaded into a `Texture` reference, caching it in memory.  The image is only
+    /// loaded the first time it is requested.  If the image cannot be loaded, the error reported by
+    /// SDL2 is returned, and the load is tried again the next time the image is requested.
+    pub fn get_image(
+        &mut self,
+        c: &mut Canvas<Window>,
+        image_name: String,
+    ) -> Result<&Texture, String> {
+        if !self.images.contains_key(&image_name) {
+            let texture = c.texture_creator().load_texture(Path::new(&image_name))?;
+
+            self.images.insert(image_name.clone(), texture);
+        }
+
+        Ok(&self.images[&image_name])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combine_opacity_multiplies_the_parent_opacity() {
+        let mut properties = WidgetProperties::default();
+
+        assert_eq!(combine_opacity(&properties, 255), 255);
+        assert_eq!(combine_opacity(&properties, 51), 51);
+
+        properties.set_value(PROPERTY_OPACITY, 128);
+
+        assert_eq!(combine_opacity(&properties, 255), 128);
+        assert_eq!(combine_opacity(&properties, 128), 64);
+        assert_eq!(combine_opacity(&properties, 0), 0);
+    }
+
+    #[test]
+    fn combine_opacity_clamps_the_property() {
+        let mut properties = WidgetProperties::default();
+
+        properties.set_value(PROPERTY_OPACITY, 300);
+        assert_eq!(combine_opacity(&properties, 255), 255);
+
+        properties.set_value(PROPERTY_OPACITY, -5);
+        assert_eq!(combine_opacity(&properties, 255), 0);
     }
 }
@@ -0,0 +1,403 @@
+// Pushrod Widgets
+// Color Parsing
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use sdl2::pixels::Color;
+
+/// This is an error that is returned when a color cannot be parsed from text.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorError {
+    /// The text is not in a recognized color format.
+    Invalid,
+
+    /// The text is in a recognized color format, but a component falls outside of its range.
+    OutOfRange,
+}
+
+/// This is the table of CSS named colors, by lower case name.
+const NAMED_COLORS: &[(&str, u32)] = &[
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
+
+/// Parses a color from text in any of the following formats:
+/// - `#RGB`, `#RGBA`, `#RRGGBB` or `#RRGGBBAA` hexadecimal notation
+/// - `rgb(r, g, b)` or `rgba(r, g, b, a)`, where components are numbers from 0 to 255 or
+///   percentages, and the alpha is a number from 0 to 1 or a percentage
+/// - `hsl(h, s%, l%)` or `hsla(h, s%, l%, a)`, where the hue is in degrees
+/// - a CSS named color (ie. `cornflowerblue`), or `transparent`
+///
+/// Components may be separated by commas or spaces.  Names and function names are not case
+/// sensitive.
+pub fn parse_color(text: &str) -> Result<Color, ColorError> {
+    let text = text.trim().to_ascii_lowercase();
+
+    if let Some(hex) = text.strip_prefix('#') {
+        return parse_hex(hex);
+    }
+
+    if let Some((function, arguments)) = split_function(&text) {
+        return match function {
+            "rgb" | "rgba" => parse_rgb(&arguments),
+            "hsl" | "hsla" => parse_hsl(&arguments),
+            _ => Err(ColorError::Invalid),
+        };
+    }
+
+    if text == "transparent" {
+        return Ok(Color::RGBA(0, 0, 0, 0));
+    }
+
+    NAMED_COLORS
+        .iter()
+        .find(|(name, _)| *name == text)
+        .map(|(_, rgb)| Color::RGB((rgb >> 16) as u8, (rgb >> 8) as u8, *rgb as u8))
+        .ok_or(ColorError::Invalid)
+}
+
+/// Splits `name(a, b, c)` into its name and list of arguments.
+fn split_function(text: &str) -> Option<(&str, Vec<&str>)> {
+    let open = text.find('(')?;
+    let arguments = text[open + 1..].strip_suffix(')')?;
+
+    Some((
+        text[..open].trim(),
+        arguments
+            .split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+            .filter(|argument| !argument.is_empty())
+            .collect(),
+    ))
+}
+
+fn parse_hex(hex: &str) -> Result<Color, ColorError> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(ColorError::Invalid);
+    }
+
+    let digit = |index: usize, width: usize| -> u8 {
+        let value = u8::from_str_radix(&hex[index * width..(index + 1) * width], 16).unwrap();
+
+        if width == 1 {
+            value * 17
+        } else {
+            value
+        }
+    };
+
+    match hex.len() {
+        3 => Ok(Color::RGB(digit(0, 1), digit(1, 1), digit(2, 1))),
+        4 => Ok(Color::RGBA(
+            digit(0, 1),
+            digit(1, 1),
+            digit(2, 1),
+            digit(3, 1),
+        )),
+        6 => Ok(Color::RGB(digit(0, 2), digit(1, 2), digit(2, 2))),
+        8 => Ok(Color::RGBA(
+            digit(0, 2),
+            digit(1, 2),
+            digit(2, 2),
+            digit(3, 2),
+        )),
+        _ => Err(ColorError::Invalid),
+    }
+}
+
+/// Parses a number, or a percentage of `scale`, checking that it falls from 0 to `scale`.
+fn parse_component(text: &str, scale: f64) -> Result<f64, ColorError> {
+    let value = match text.strip_suffix('%') {
+        Some(percentage) => {
+            percentage.parse::<f64>().map_err(|_| ColorError::Invalid)? * scale / 100.0
+        }
+        None => text.parse::<f64>().map_err(|_| ColorError::Invalid)?,
+    };
+
+    if value.is_nan() || !(0.0..=scale).contains(&value) {
+        Err(ColorError::OutOfRange)
+    } else {
+        Ok(value)
+    }
+}
+
+/// Parses the optional alpha argument at `index`, returning it scaled from 0 to 255.
+fn parse_alpha(arguments: &[&str], index: usize) -> Result<u8, ColorError> {
+    match arguments.get(index) {
+        Some(alpha) => Ok((parse_component(alpha, 1.0)? * 255.0).round() as u8),
+        None => Ok(255),
+    }
+}
+
+fn parse_rgb(arguments: &[&str]) -> Result<Color, ColorError> {
+    if arguments.len() != 3 && arguments.len() != 4 {
+        return Err(ColorError::Invalid);
+    }
+
+    let channel = |index: usize| -> Result<u8, ColorError> {
+        Ok(parse_component(arguments[index], 255.0)?.round() as u8)
+    };
+
+    Ok(Color::RGBA(
+        channel(0)?,
+        channel(1)?,
+        channel(2)?,
+        parse_alpha(arguments, 3)?,
+    ))
+}
+
+fn parse_hsl(arguments: &[&str]) -> Result<Color, ColorError> {
+    if arguments.len() != 3 && arguments.len() != 4 {
+        return Err(ColorError::Invalid);
+    }
+
+    let hue = arguments[0]
+        .strip_suffix("deg")
+        .unwrap_or(arguments[0])
+        .parse::<f64>()
+        .map_err(|_| ColorError::Invalid)?
+        .rem_euclid(360.0);
+    let saturation = parse_component(arguments[1], 100.0)? / 100.0;
+    let lightness = parse_component(arguments[2], 100.0)? / 100.0;
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let secondary = chroma * (1.0 - ((hue / 60.0) % 2.0 - 1.0).abs());
+    let offset = lightness - chroma / 2.0;
+    let (r, g, b) = match (hue / 60.0) as u32 {
+        0 => (chroma, secondary, 0.0),
+        1 => (secondary, chroma, 0.0),
+        2 => (0.0, chroma, secondary),
+        3 => (0.0, secondary, chroma),
+        4 => (secondary, 0.0, chroma),
+        _ => (chroma, 0.0, secondary),
+    };
+    let channel = |value: f64| ((value + offset) * 255.0).round() as u8;
+
+    Ok(Color::RGBA(
+        channel(r),
+        channel(g),
+        channel(b),
+        parse_alpha(arguments, 3)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_color_reads_hex_notation() {
+        assert_eq!(parse_color("#f00"), Ok(Color::RGB(255, 0, 0)));
+        assert_eq!(parse_color("#f008"), Ok(Color::RGBA(255, 0, 0, 136)));
+        assert_eq!(parse_color("#336699"), Ok(Color::RGB(0x33, 0x66, 0x99)));
+        assert_eq!(
+            parse_color(" #33669980 "),
+            Ok(Color::RGBA(0x33, 0x66, 0x99, 0x80))
+        );
+        assert_eq!(parse_color("#12345"), Err(ColorError::Invalid));
+        assert_eq!(parse_color("#ggg"), Err(ColorError::Invalid));
+    }
+
+    #[test]
+    fn parse_color_reads_rgb_functions() {
+        assert_eq!(parse_color("rgb(10, 20, 30)"), Ok(Color::RGB(10, 20, 30)));
+        assert_eq!(
+            parse_color("RGBA(10 20 30 / 50%)"),
+            Ok(Color::RGBA(10, 20, 30, 128))
+        );
+        assert_eq!(
+            parse_color("rgb(100%, 0%, 50%)"),
+            Ok(Color::RGB(255, 0, 128))
+        );
+        assert_eq!(parse_color("rgb(256, 0, 0)"), Err(ColorError::OutOfRange));
+        assert_eq!(parse_color("rgba(0, 0, 0, 2)"), Err(ColorError::OutOfRange));
+        assert_eq!(parse_color("rgb(0, 0)"), Err(ColorError::Invalid));
+        assert_eq!(parse_color("rgb(red, 0, 0)"), Err(ColorError::Invalid));
+    }
+
+    #[test]
+    fn parse_color_reads_hsl_functions() {
+        assert_eq!(parse_color("hsl(0, 100%, 50%)"), Ok(Color::RGB(255, 0, 0)));
+        assert_eq!(
+            parse_color("hsl(120deg, 100%, 25%)"),
+            Ok(Color::RGB(0, 128, 0))
+        );
+        assert_eq!(
+            parse_color("hsla(-120, 100%, 50%, 0.5)"),
+            Ok(Color::RGBA(0, 0, 255, 128))
+        );
+        assert_eq!(
+            parse_color("hsl(0, 0%, 100%)"),
+            Ok(Color::RGB(255, 255, 255))
+        );
+        assert_eq!(
+            parse_color("hsl(0, 150%, 50%)"),
+            Err(ColorError::OutOfRange)
+        );
+    }
+
+    #[test]
+    fn parse_color_reads_names() {
+        assert_eq!(
+            parse_color("CornflowerBlue"),
+            Ok(Color::RGB(0x64, 0x95, 0xed))
+        );
+        assert_eq!(parse_color("transparent"), Ok(Color::RGBA(0, 0, 0, 0)));
+        assert_eq!(parse_color("notacolor"), Err(ColorError::Invalid));
+        assert_eq!(parse_color(""), Err(ColorError::Invalid));
+    }
+}
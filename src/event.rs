@@ -0,0 +1,566 @@
+// Pushrod Widgets
+// Events
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::properties::{WidgetProperties, PROPERTY_DRAG_THRESHOLD};
+use sdl2::event::Event;
+use sdl2::keyboard::{Keycode, Mod, Scancode};
+use sdl2::mouse::{MouseButton, MouseWheelDirection};
+
+/// This is an input event delivered to a `Widget` through `Widget::handle_event`.  Mouse and
+/// keyboard events are converted from the `Event`s generated by SDL2, using `from_sdl_event`.
+/// Mouse positions are in window coordinates, the same as the origin of a `Widget`.  Focus and
+/// enter/leave events are not generated by SDL2 for `Widget`s; they are sent to a `Widget` when
+/// it gains or loses the keyboard focus, or when the pointer moves onto or off of it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WidgetEvent {
+    /// A mouse button was pressed at the given position.  `clicks` is `1` for a single click, `2`
+    /// for a double click, and so on.
+    MouseDown {
+        button: MouseButton,
+        x: i32,
+        y: i32,
+        clicks: u8,
+    },
+
+    /// A mouse button was released at the given position.
+    MouseUp {
+        button: MouseButton,
+        x: i32,
+        y: i32,
+        clicks: u8,
+    },
+
+    /// The pointer moved to the given position, by `xrel` and `yrel` pixels.
+    MouseMove {
+        x: i32,
+        y: i32,
+        xrel: i32,
+        yrel: i32,
+    },
+
+    /// The mouse wheel was scrolled by the given amount.  Positive values of `y` scroll away from
+    /// the user, and positive values of `x` scroll to the right, regardless of whether the
+    /// system flips the direction of the wheel.
+    MouseWheel { x: i32, y: i32 },
+
+    /// A key was pressed.  `repeat` is set if the key is being held down, and the press has been
+    /// repeated by the keyboard.
+    KeyDown {
+        keycode: Option<Keycode>,
+        scancode: Option<Scancode>,
+        keymod: Mod,
+        repeat: bool,
+    },
+
+    /// A key was released.
+    KeyUp {
+        keycode: Option<Keycode>,
+        scancode: Option<Scancode>,
+        keymod: Mod,
+    },
+
+    /// Text was typed, after the keyboard layout and input method have been applied.
+    TextInput { text: String },
+
+    /// The `Widget` has gained the keyboard focus.
+    FocusGained,
+
+    /// The `Widget` has lost the keyboard focus.
+    FocusLost,
+
+    /// The pointer has moved onto the `Widget`.
+    Enter,
+
+    /// The pointer has moved off of the `Widget`.
+    Leave,
+
+    /// The pointer has moved far enough from the point at which the `Widget` captured it, while
+    /// a mouse button is held down, for the movement to be treated as a drag.  The position is the
+    /// point at which the drag started.
+    DragStart { button: MouseButton, x: i32, y: i32 },
+
+    /// The pointer has moved during a drag, to the given position.  `start` is the point at which
+    /// the drag started, and `dx` and `dy` are the distance moved from it.
+    Drag {
+        button: MouseButton,
+        start: (i32, i32),
+        x: i32,
+        y: i32,
+        dx: i32,
+        dy: i32,
+    },
+
+    /// The mouse button was released at the given position, ending a drag.  `start` is the point
+    /// at which the drag started, and `dx` and `dy` are the distance moved from it.
+    DragEnd {
+        button: MouseButton,
+        start: (i32, i32),
+        x: i32,
+        y: i32,
+        dx: i32,
+        dy: i32,
+    },
+}
+
+/// This is the implementation of the `WidgetEvent`.
+impl WidgetEvent {
+    /// Converts an SDL2 `Event` to a `WidgetEvent`, or returns `None` if the `Event` is not an
+    /// input event that `Widget`s handle (such as a window or quit event.)
+    pub fn from_sdl_event(event: &Event) -> Option<Self> {
+        match event {
+            Event::MouseButtonDown {
+                mouse_btn,
+                clicks,
+                x,
+                y,
+                ..
+            } => Some(WidgetEvent::MouseDown {
+                button: *mouse_btn,
+                x: *x,
+                y: *y,
+                clicks: *clicks,
+            }),
+            Event::MouseButtonUp {
+                mouse_btn,
+                clicks,
+                x,
+                y,
+                ..
+            } => Some(WidgetEvent::MouseUp {
+                button: *mouse_btn,
+                x: *x,
+                y: *y,
+                clicks: *clicks,
+            }),
+            Event::MouseMotion {
+                x, y, xrel, yrel, ..
+            } => Some(WidgetEvent::MouseMove {
+                x: *x,
+                y: *y,
+                xrel: *xrel,
+                yrel: *yrel,
+            }),
+            Event::MouseWheel {
+                x, y, direction, ..
+            } => {
+                let sign = if *direction == MouseWheelDirection::Flipped {
+                    -1
+                } else {
+                    1
+                };
+
+                Some(WidgetEvent::MouseWheel {
+                    x: x * sign,
+                    y: y * sign,
+                })
+            }
+            Event::KeyDown {
+                keycode,
+                scancode,
+                keymod,
+                repeat,
+                ..
+            } => Some(WidgetEvent::KeyDown {
+                keycode: *keycode,
+                scancode: *scancode,
+                keymod: *keymod,
+                repeat: *repeat,
+            }),
+            Event::KeyUp {
+                keycode,
+                scancode,
+                keymod,
+                ..
+            } => Some(WidgetEvent::KeyUp {
+                keycode: *keycode,
+                scancode: *scancode,
+                keymod: *keymod,
+            }),
+            Event::TextInput { text, .. } => Some(WidgetEvent::TextInput { text: text.clone() }),
+            _ => None,
+        }
+    }
+
+    /// Returns the position of the pointer for events that have one, or `None` for all other
+    /// events.
+    pub fn position(&self) -> Option<(i32, i32)> {
+        match self {
+            WidgetEvent::MouseDown { x, y, .. }
+            | WidgetEvent::MouseUp { x, y, .. }
+            | WidgetEvent::MouseMove { x, y, .. }
+            | WidgetEvent::DragStart { x, y, .. }
+            | WidgetEvent::Drag { x, y, .. }
+            | WidgetEvent::DragEnd { x, y, .. } => Some((*x, *y)),
+            _ => None,
+        }
+    }
+}
+
+/// This is a recognizer for drag gestures, used by the `WidgetCache` while a `Widget` has
+/// captured the pointer with a mouse button held down.  The pointer must move at least
+/// `threshold` pixels from the `start` point before a drag begins, so that small movements during
+/// a click are not treated as a drag.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DragGesture {
+    pub button: MouseButton,
+    pub start: (i32, i32),
+    pub threshold: u32,
+
+    /// Whether or not the pointer has moved past the threshold, starting the drag.
+    pub dragging: bool,
+}
+
+/// This is the implementation of the `DragGesture`.
+impl DragGesture {
+    /// Creates a `DragGesture` for the given `button`, pressed at the `start` point.
+    pub fn new(button: MouseButton, start: (i32, i32), threshold: u32) -> Self {
+        Self {
+            button,
+            start,
+            threshold,
+            dragging: false,
+        }
+    }
+
+    /// Creates a `DragGesture` for the given `button`, pressed at the `start` point, with the
+    /// threshold set by `PROPERTY_DRAG_THRESHOLD` in the given properties, or its registered
+    /// default of 4 pixels.
+    pub fn from_properties(
+        button: MouseButton,
+        start: (i32, i32),
+        properties: &WidgetProperties,
+    ) -> Self {
+        let threshold = properties.get_value(PROPERTY_DRAG_THRESHOLD).max(0) as u32;
+
+        Self::new(button, start, threshold)
+    }
+
+    /// Returns the events generated by the pointer moving to the given position: a
+    /// `WidgetEvent::DragStart` followed by a `WidgetEvent::Drag` when the pointer first moves
+    /// past the threshold, and a `WidgetEvent::Drag` on each move after that.
+    pub fn moved(&mut self, x: i32, y: i32) -> Vec<WidgetEvent> {
+        let (dx, dy) = (x - self.start.0, y - self.start.1);
+        let mut events = Vec::new();
+
+        if !self.dragging {
+            let distance = i64::from(dx).pow(2) + i64::from(dy).pow(2);
+
+            if distance < i64::from(self.threshold).pow(2) {
+                return events;
+            }
+
+            self.dragging = true;
+            events.push(WidgetEvent::DragStart {
+                button: self.button,
+                x: self.start.0,
+                y: self.start.1,
+            });
+        }
+
+        events.push(WidgetEvent::Drag {
+            button: self.button,
+            start: self.start,
+            x,
+            y,
+            dx,
+            dy,
+        });
+        events
+    }
+
+    /// Returns the `WidgetEvent::DragEnd` generated by the button being released at the given
+    /// position, or `None` if the pointer never moved past the threshold.
+    pub fn released(&self, x: i32, y: i32) -> Option<WidgetEvent> {
+        if self.dragging {
+            Some(WidgetEvent::DragEnd {
+                button: self.button,
+                start: self.start,
+                x,
+                y,
+                dx: x - self.start.0,
+                dy: y - self.start.1,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+/// This is the phase of the dispatch of a `WidgetEvent` through the tree of `Widget`s.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EventPhase {
+    /// The event is passed down from the root `Widget` to the parent of its target, so that
+    /// containers can intercept events before they reach their children.
+    Capture,
+
+    /// The event is passed to its target.
+    Target,
+
+    /// The event is passed back up from the parent of its target to the root `Widget`, so that
+    /// containers can handle events that their children did not consume.
+    Bubble,
+}
+
+/// This is the context in which a `Widget` handles a `WidgetEvent`, passed to
+/// `Widget::handle_event`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EventContext {
+    /// The ID of the `Widget` handling the event.
+    pub widget_id: u32,
+
+    /// The ID of the `Widget` the event is targeted at.  This is the same as `widget_id`, unless
+    /// the event is passed to the `Widget` on its way to or from one of its children.
+    pub target_id: u32,
+
+    /// The phase of the dispatch.  `Widget`s that only handle events targeted at themselves
+    /// should ignore events in the `EventPhase::Capture` phase, as consuming them prevents the
+    /// event from reaching the target.
+    pub phase: EventPhase,
+
+    /// Whether the `Widget` handling the event asked to capture (`true`) or release (`false`) the
+    /// pointer, or `None` if it made no request.
+    pub(crate) capture: Option<bool>,
+}
+
+/// This is the implementation of the `EventContext`.
+impl EventContext {
+    /// Creates a context for an event targeted at the `Widget` with the given ID, which handles
+    /// the event itself.
+    pub fn new(widget_id: u32) -> Self {
+        Self {
+            widget_id,
+            target_id: widget_id,
+            phase: EventPhase::Target,
+            capture: None,
+        }
+    }
+
+    /// Captures the pointer for the `Widget` handling the event, so that all mouse events are
+    /// sent to it, even when the pointer is outside of its bounds, until it releases the pointer
+    /// or the mouse button is released.  If a mouse button is held down, the `Widget` is also sent
+    /// drag events as the pointer moves; see `DragGesture`.
+    pub fn capture_pointer(&mut self) {
+        self.capture = Some(true);
+    }
+
+    /// Releases the pointer, if it has been captured by the `Widget` handling the event.
+    pub fn release_pointer(&mut self) {
+        self.capture = Some(false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_sdl_event_converts_mouse_events() {
+        let down = Event::MouseButtonDown {
+            timestamp: 0,
+            window_id: 1,
+            which: 0,
+            mouse_btn: MouseButton::Left,
+            clicks: 2,
+            x: 10,
+            y: 20,
+        };
+        let motion = Event::MouseMotion {
+            timestamp: 0,
+            window_id: 1,
+            which: 0,
+            mousestate: sdl2::mouse::MouseState::from_sdl_state(0),
+            x: 15,
+            y: 25,
+            xrel: 5,
+            yrel: 5,
+        };
+
+        assert_eq!(
+            WidgetEvent::from_sdl_event(&down),
+            Some(WidgetEvent::MouseDown {
+                button: MouseButton::Left,
+                x: 10,
+                y: 20,
+                clicks: 2,
+            })
+        );
+        assert_eq!(
+            WidgetEvent::from_sdl_event(&motion),
+            Some(WidgetEvent::MouseMove {
+                x: 15,
+                y: 25,
+                xrel: 5,
+                yrel: 5,
+            })
+        );
+    }
+
+    #[test]
+    fn from_sdl_event_unflips_the_mouse_wheel() {
+        let wheel = |direction: MouseWheelDirection| Event::MouseWheel {
+            timestamp: 0,
+            window_id: 1,
+            which: 0,
+            x: 1,
+            y: -2,
+            direction,
+        };
+
+        assert_eq!(
+            WidgetEvent::from_sdl_event(&wheel(MouseWheelDirection::Normal)),
+            Some(WidgetEvent::MouseWheel { x: 1, y: -2 })
+        );
+        assert_eq!(
+            WidgetEvent::from_sdl_event(&wheel(MouseWheelDirection::Flipped)),
+            Some(WidgetEvent::MouseWheel { x: -1, y: 2 })
+        );
+    }
+
+    #[test]
+    fn from_sdl_event_converts_keyboard_events() {
+        let key_down = Event::KeyDown {
+            timestamp: 0,
+            window_id: 1,
+            keycode: Some(Keycode::Tab),
+            scancode: Some(Scancode::Tab),
+            keymod: Mod::LSHIFTMOD,
+            repeat: true,
+        };
+        let text = Event::TextInput {
+            timestamp: 0,
+            window_id: 1,
+            text: String::from("a"),
+        };
+
+        assert_eq!(
+            WidgetEvent::from_sdl_event(&key_down),
+            Some(WidgetEvent::KeyDown {
+                keycode: Some(Keycode::Tab),
+                scancode: Some(Scancode::Tab),
+                keymod: Mod::LSHIFTMOD,
+                repeat: true,
+            })
+        );
+        assert_eq!(
+            WidgetEvent::from_sdl_event(&text),
+            Some(WidgetEvent::TextInput {
+                text: String::from("a")
+            })
+        );
+    }
+
+    #[test]
+    fn from_sdl_event_ignores_other_events() {
+        assert_eq!(
+            WidgetEvent::from_sdl_event(&Event::Quit { timestamp: 0 }),
+            None
+        );
+    }
+
+    #[test]
+    fn drag_gesture_waits_for_the_threshold() {
+        let mut drag = DragGesture::new(MouseButton::Left, (10, 10), 4);
+
+        assert!(drag.moved(12, 12).is_empty());
+        assert_eq!(drag.released(12, 12), None);
+        assert!(!drag.dragging);
+
+        assert_eq!(
+            drag.moved(14, 10),
+            vec![
+                WidgetEvent::DragStart {
+                    button: MouseButton::Left,
+                    x: 10,
+                    y: 10,
+                },
+                WidgetEvent::Drag {
+                    button: MouseButton::Left,
+                    start: (10, 10),
+                    x: 14,
+                    y: 10,
+                    dx: 4,
+                    dy: 0,
+                },
+            ]
+        );
+        assert!(drag.dragging);
+    }
+
+    #[test]
+    fn drag_gesture_continues_and_ends_after_starting() {
+        let mut drag = DragGesture::new(MouseButton::Right, (0, 0), 0);
+
+        assert_eq!(drag.moved(0, 0).len(), 2);
+        assert_eq!(
+            drag.moved(-3, 2),
+            vec![WidgetEvent::Drag {
+                button: MouseButton::Right,
+                start: (0, 0),
+                x: -3,
+                y: 2,
+                dx: -3,
+                dy: 2,
+            }]
+        );
+        assert_eq!(
+            drag.released(5, 5),
+            Some(WidgetEvent::DragEnd {
+                button: MouseButton::Right,
+                start: (0, 0),
+                x: 5,
+                y: 5,
+                dx: 5,
+                dy: 5,
+            })
+        );
+    }
+
+    #[test]
+    fn widget_event_position_is_reported_for_pointer_events() {
+        assert_eq!(
+            WidgetEvent::MouseMove {
+                x: 1,
+                y: 2,
+                xrel: 0,
+                yrel: 0
+            }
+            .position(),
+            Some((1, 2))
+        );
+        assert_eq!(WidgetEvent::MouseWheel { x: 0, y: 1 }.position(), None);
+    }
+
+    #[test]
+    fn drag_gesture_uses_the_default_threshold() {
+        let mut drag =
+            DragGesture::from_properties(MouseButton::Left, (0, 0), &WidgetProperties::default());
+
+        assert_eq!(drag.threshold, 4);
+        assert!(drag.moved(2, 0).is_empty());
+        assert!(!drag.dragging);
+        assert_eq!(drag.moved(4, 0).len(), 2);
+    }
+
+    #[test]
+    fn drag_gesture_uses_the_threshold_property() {
+        let mut properties = WidgetProperties::default();
+
+        properties.set_value(PROPERTY_DRAG_THRESHOLD, 1);
+
+        let mut drag = DragGesture::from_properties(MouseButton::Left, (0, 0), &properties);
+
+        assert_eq!(drag.moved(2, 0).len(), 2);
+    }
+}
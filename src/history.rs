@@ -0,0 +1,216 @@
+// Pushrod Widgets
+// History
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::caches::PropertyChange;
+
+/// This is a single recorded change to a `WidgetCache`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HistoryEntry {
+    /// A property of a `Widget` was changed.
+    Property(PropertyChange),
+
+    /// A `Widget` was added to its parent, at the given position in the parent's list of children.
+    Added {
+        widget_id: u32,
+        parent_id: u32,
+        position: usize,
+    },
+}
+
+/// This is a record of changes made to a `WidgetCache`, grouped into steps that can be undone and
+/// redone.  Changes are grouped into a single step between calls to `begin_step` and `end_step`;
+/// outside of these, each change made through the `WidgetCache` becomes a step of its own.
+///
+/// Discarding a step does not free anything it refers to: `Widget`s whose addition was undone
+/// remain detached in the `WidgetCache`, along with their textures.
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    undo_steps: Vec<Vec<HistoryEntry>>,
+    redo_steps: Vec<Vec<HistoryEntry>>,
+    open_step: Option<Vec<HistoryEntry>>,
+}
+
+/// This is the implementation of the `History` store.
+impl History {
+    /// Starts a step, grouping all changes recorded until `end_step` is called.  If a step is
+    /// already open, it remains open.
+    pub fn begin_step(&mut self) {
+        if self.open_step.is_none() {
+            self.open_step = Some(Vec::new());
+        }
+    }
+
+    /// Ends a step started by `begin_step`.  If no changes were recorded, no step is added.
+    pub fn end_step(&mut self) {
+        if let Some(step) = self.open_step.take() {
+            self.push_step(step);
+        }
+    }
+
+    /// Records a group of changes.  If a step is open, the changes are added to it, otherwise, they
+    /// are added as a new step.  Recording a change discards all steps that could be redone.
+    pub fn record(&mut self, entries: Vec<HistoryEntry>) {
+        if entries.is_empty() {
+            return;
+        }
+
+        match self.open_step.as_mut() {
+            Some(step) => step.extend(entries),
+            None => self.push_step(entries),
+        }
+
+        self.redo_steps.clear();
+    }
+
+    /// Returns a flag indicating whether or not there is a step that can be undone.
+    pub fn can_undo(&self) -> bool {
+        !self.undo_steps.is_empty()
+    }
+
+    /// Returns a flag indicating whether or not there is a step that can be redone.
+    pub fn can_redo(&self) -> bool {
+        !self.redo_steps.is_empty()
+    }
+
+    /// Removes the most recent step, and moves it to the list of steps that can be redone.  Any
+    /// open step is ended first.
+    pub fn take_undo(&mut self) -> Option<Vec<HistoryEntry>> {
+        self.end_step();
+
+        let step = self.undo_steps.pop()?;
+
+        self.redo_steps.push(step.clone());
+        Some(step)
+    }
+
+    /// Removes the most recently undone step, and moves it back to the list of steps that can be
+    /// undone.
+    pub fn take_redo(&mut self) -> Option<Vec<HistoryEntry>> {
+        let step = self.redo_steps.pop()?;
+
+        self.undo_steps.push(step.clone());
+        Some(step)
+    }
+
+    /// Removes all recorded steps.
+    pub fn clear(&mut self) {
+        self.undo_steps.clear();
+        self.redo_steps.clear();
+        self.open_step = None;
+    }
+
+    fn push_step(&mut self, step: Vec<HistoryEntry>) {
+        if !step.is_empty() {
+            self.undo_steps.push(step);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::properties::PropertyValue;
+
+    fn change(widget_id: u32, value: i32) -> HistoryEntry {
+        HistoryEntry::Property(PropertyChange {
+            widget_id,
+            property_key: 1,
+            old_value: None,
+            new_value: Some(PropertyValue::Integer(value)),
+        })
+    }
+
+    #[test]
+    fn changes_outside_of_a_step_are_steps_of_their_own() {
+        let mut history = History::default();
+
+        history.record(vec![change(1, 1)]);
+        history.record(vec![change(1, 2)]);
+
+        assert_eq!(history.take_undo(), Some(vec![change(1, 2)]));
+        assert_eq!(history.take_undo(), Some(vec![change(1, 1)]));
+        assert_eq!(history.take_undo(), None);
+    }
+
+    #[test]
+    fn changes_within_a_step_are_grouped() {
+        let mut history = History::default();
+
+        history.begin_step();
+        history.record(vec![change(1, 1)]);
+        history.begin_step();
+        history.record(vec![change(2, 2), change(3, 3)]);
+        history.end_step();
+
+        assert_eq!(
+            history.take_undo(),
+            Some(vec![change(1, 1), change(2, 2), change(3, 3)])
+        );
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn empty_steps_are_not_recorded() {
+        let mut history = History::default();
+
+        history.begin_step();
+        history.record(Vec::new());
+        history.end_step();
+
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn take_undo_ends_an_open_step() {
+        let mut history = History::default();
+
+        history.begin_step();
+        history.record(vec![change(1, 1)]);
+
+        assert_eq!(history.take_undo(), Some(vec![change(1, 1)]));
+        assert!(history.can_redo());
+    }
+
+    #[test]
+    fn undone_steps_are_redone_in_reverse_order() {
+        let mut history = History::default();
+
+        history.record(vec![change(1, 1)]);
+        history.record(vec![change(1, 2)]);
+        history.take_undo();
+        history.take_undo();
+
+        assert_eq!(history.take_redo(), Some(vec![change(1, 1)]));
+        assert_eq!(history.take_redo(), Some(vec![change(1, 2)]));
+        assert_eq!(history.take_redo(), None);
+        assert_eq!(history.take_undo(), Some(vec![change(1, 2)]));
+    }
+
+    #[test]
+    fn recording_a_change_clears_the_steps_to_redo() {
+        let mut history = History::default();
+
+        history.record(vec![change(1, 1)]);
+        history.take_undo();
+
+        assert!(history.can_redo());
+
+        history.record(vec![change(1, 2)]);
+
+        assert!(!history.can_redo());
+        assert_eq!(history.take_redo(), None);
+        assert_eq!(history.take_undo(), Some(vec![change(1, 2)]));
+    }
+}
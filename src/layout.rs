@@ -0,0 +1,1032 @@
+// Pushrod Widgets
+// Layout
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::properties::{
+    PropertyValue, Sides, WidgetProperties, PROPERTY_ALIGNMENT, PROPERTY_ANCHORS,
+    PROPERTY_MAX_SIZE, PROPERTY_MIN_SIZE, PROPERTY_PREFERRED_SIZE, PROPERTY_RELATIVE_ORIGIN,
+    PROPERTY_RELATIVE_SIZE, PROPERTY_STRETCH,
+};
+use sdl2::rect::Rect;
+use std::collections::HashSet;
+
+/// This is a size, as a width and height in pixels.
+pub type Size = (u32, u32);
+
+/// This is a cell of a grid, as its row, column, row span and column span.
+pub type GridCell = (u32, u32, u32, u32);
+
+/// This is the largest number of rows or columns a grid can have.  Cells that extend beyond it are
+/// rejected by `WidgetProperties::try_get_grid_cell`.
+pub const GRID_TRACK_LIMIT: u32 = 1024;
+
+/// This is a length that is either a number of pixels, or a percentage of the length of the
+/// parent's content area.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Length {
+    Pixels(i32),
+    Percent(f64),
+}
+
+/// This is the implementation of `Length`.
+impl Length {
+    /// Parses a `Length` from text, such as `"120"`, `"120px"` or `"50%"`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+
+        if let Some(percent) = text.strip_suffix('%') {
+            percent.trim().parse().ok().map(Length::Percent)
+        } else {
+            text.strip_suffix("px")
+                .unwrap_or(text)
+                .trim()
+                .parse()
+                .ok()
+                .map(Length::Pixels)
+        }
+    }
+
+    /// Resolves the `Length` to a number of pixels, where `total` is the length a percentage is
+    /// taken of.
+    pub fn resolve(&self, total: i32) -> i32 {
+        match self {
+            Length::Pixels(pixels) => *pixels,
+            Length::Percent(percent) => (f64::from(total) * percent / 100.0).round() as i32,
+        }
+    }
+}
+
+/// This describes where a `Widget` is placed within the content area of its parent, as set by
+/// `PROPERTY_RELATIVE_SIZE`, `PROPERTY_RELATIVE_ORIGIN` and `PROPERTY_ANCHORS`.  The placement is
+/// resolved by the `WidgetCache` into the `Widget`'s `PROPERTY_ORIGIN` and `PROPERTY_SIZE`
+/// whenever the bounds of its parent change.
+///
+/// For each axis, a `Widget` anchored to both edges stretches between them.  A `Widget` anchored
+/// to one edge is kept at the given distance from it, using its relative or absolute size.  A
+/// `Widget` that is not anchored is placed at its relative origin, if it has one, and otherwise
+/// keeps its absolute origin.  The `Widget`'s margin is added to its distance from the edges.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Placement {
+    pub size: (Option<Length>, Option<Length>),
+    pub origin: (Option<Length>, Option<Length>),
+    pub anchors: Sides<Option<Length>>,
+}
+
+/// This is the implementation of the `Placement`.
+impl Placement {
+    /// Reads a `Placement` from the properties of a `Widget`.  Returns `None` if none of the
+    /// placement properties have been set, as the `Widget` is placed with absolute coordinates.
+    ///
+    /// Sizes and origins are stored as two lengths, separated by spaces (ie. `"50% 100%"`), or as
+    /// a list.  Anchors are stored as one, two or four lengths, in the same order as
+    /// `WidgetProperties::try_get_sides`, where `auto` leaves an edge unanchored (ie.
+    /// `"10 10 auto 10"` anchors every edge except the bottom).
+    pub fn from_properties(properties: &WidgetProperties) -> Option<Self> {
+        let size = properties.get(PROPERTY_RELATIVE_SIZE).and_then(lengths);
+        let origin = properties.get(PROPERTY_RELATIVE_ORIGIN).and_then(lengths);
+        let anchors = properties.get(PROPERTY_ANCHORS).and_then(lengths);
+
+        if size.is_none() && origin.is_none() && anchors.is_none() {
+            return None;
+        }
+
+        let pair = |values: Option<Vec<Option<Length>>>| match values.as_deref() {
+            Some([x, y]) => (*x, *y),
+            Some([both]) => (*both, *both),
+            _ => (None, None),
+        };
+
+        Some(Self {
+            size: pair(size),
+            origin: pair(origin),
+            anchors: anchors
+                .and_then(|anchors| Sides::from_shorthand(&anchors))
+                .unwrap_or(Sides::uniform(None)),
+        })
+    }
+
+    /// Resolves the `Placement` within the `area` of the parent's content, returning the origin
+    /// and size of the `Widget`.  The current `origin` and `size` are used for any values the
+    /// `Placement` does not specify.
+    pub fn resolve(
+        &self,
+        area: Rect,
+        margin: Sides<i32>,
+        origin: (i32, i32),
+        size: (u32, u32),
+    ) -> ((i32, i32), (u32, u32)) {
+        let (x, width) = resolve_axis(
+            (area.x(), area.width() as i32),
+            (self.anchors.left, self.anchors.right),
+            (margin.left, margin.right),
+            (self.origin.0, self.size.0),
+            (origin.0, size.0),
+        );
+        let (y, height) = resolve_axis(
+            (area.y(), area.height() as i32),
+            (self.anchors.top, self.anchors.bottom),
+            (margin.top, margin.bottom),
+            (self.origin.1, self.size.1),
+            (origin.1, size.1),
+        );
+
+        ((x, y), (width, height))
+    }
+}
+
+/// Resolves the position and length of a `Widget` along one axis, given the start and extent of
+/// the parent's content area, the anchors and margins of the near (left or top) and far (right or
+/// bottom) edges, the relative position and length, and the current position and length.
+fn resolve_axis(
+    (start, extent): (i32, i32),
+    (near, far): (Option<Length>, Option<Length>),
+    (margin_near, margin_far): (i32, i32),
+    (position, length): (Option<Length>, Option<Length>),
+    (current_position, current_length): (i32, u32),
+) -> (i32, u32) {
+    let length = length
+        .map(|length| length.resolve(extent).max(0) as u32)
+        .unwrap_or(current_length);
+
+    match (near, far) {
+        (Some(near), Some(far)) => {
+            let near = near.resolve(extent) + margin_near;
+            let far = far.resolve(extent) + margin_far;
+
+            (start + near, (extent - near - far).max(0) as u32)
+        }
+        (Some(near), None) => (start + near.resolve(extent) + margin_near, length),
+        (None, Some(far)) => (
+            start + extent - far.resolve(extent) - margin_far - length as i32,
+            length,
+        ),
+        (None, None) => match position {
+            Some(position) => (start + position.resolve(extent) + margin_near, length),
+            None => (current_position, length),
+        },
+    }
+}
+
+/// Reads a list of lengths from a property value, where `auto` is read as `None`.  Returns `None`
+/// if any of the lengths cannot be read.
+fn lengths(value: &PropertyValue) -> Option<Vec<Option<Length>>> {
+    let length = |text: &str| {
+        if text.trim().eq_ignore_ascii_case("auto") {
+            Some(None)
+        } else {
+            Length::parse(text).map(Some)
+        }
+    };
+
+    match value {
+        PropertyValue::Integer(pixels) => Some(vec![Some(Length::Pixels(*pixels))]),
+        PropertyValue::Pair(x, y) => Some(vec![
+            Some(Length::Pixels(*x as i32)),
+            Some(Length::Pixels(*y as i32)),
+        ]),
+        PropertyValue::Point(x, y) => {
+            Some(vec![Some(Length::Pixels(*x)), Some(Length::Pixels(*y))])
+        }
+        PropertyValue::String(text) => text.split_whitespace().map(length).collect(),
+        PropertyValue::List(items) => items
+            .iter()
+            .map(|item| match item {
+                PropertyValue::Integer(pixels) => Some(Some(Length::Pixels(*pixels))),
+                PropertyValue::String(text) => length(text),
+                _ => None,
+            })
+            .collect(),
+        _ => None,
+    }
+}
+
+/// This is the direction in which a box container places its children.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Axis {
+    Horizontal,
+    Vertical,
+}
+
+/// This is the alignment of a `Widget` within the space its container gives it, as set on the
+/// container by `PROPERTY_ALIGNMENT`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Alignment {
+    /// The `Widget` is placed at the left or top of the space.
+    Start,
+
+    /// The `Widget` is placed in the center of the space.
+    Center,
+
+    /// The `Widget` is placed at the right or bottom of the space.
+    End,
+
+    /// The `Widget` is sized to fill the space, within its minimum and maximum sizes.
+    Stretch,
+}
+
+/// This is the implementation of `Alignment`.
+impl Alignment {
+    /// Retrieves an `Alignment` by its name (ie. `"center"`), or `None` if the name is unknown.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "start" | "left" | "top" => Some(Alignment::Start),
+            "center" => Some(Alignment::Center),
+            "end" | "right" | "bottom" => Some(Alignment::End),
+            "stretch" => Some(Alignment::Stretch),
+            _ => None,
+        }
+    }
+
+    /// Reads the `Alignment` a container gives its children from `PROPERTY_ALIGNMENT`.  An unknown
+    /// alignment is read as `Alignment::Stretch`.
+    pub fn from_properties(properties: &WidgetProperties) -> Self {
+        Self::from_name(&properties.get_string(PROPERTY_ALIGNMENT)).unwrap_or(Alignment::Stretch)
+    }
+}
+
+/// This describes the space a child `Widget` asks its container for, read from the child's
+/// properties.  Containers use it to size and place their children in `Widget::arrange`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LayoutItem {
+    /// The size the `Widget` would like to be.
+    pub preferred: (u32, u32),
+    pub min: (u32, u32),
+    pub max: (u32, u32),
+
+    /// The share of any space left over in a box or grid that the `Widget` takes, relative to
+    /// its siblings.  A `Widget` with a stretch factor of `0` keeps its preferred size.
+    pub stretch: u32,
+    pub margin: Sides<i32>,
+
+    /// The row, column, row span and column span of the `Widget` in a grid, or `None` if it is
+    /// placed in the next free cell.
+    pub cell: Option<GridCell>,
+}
+
+/// This is the implementation of the `LayoutItem`.
+impl LayoutItem {
+    /// Reads a `LayoutItem` from the properties of a `Widget`.  The preferred size is read from
+    /// `PROPERTY_PREFERRED_SIZE`, falling back to the current size of the `Widget`.
+    pub fn from_properties(properties: &WidgetProperties) -> Self {
+        Self {
+            preferred: properties
+                .try_get_pair(PROPERTY_PREFERRED_SIZE)
+                .unwrap_or_else(|_| properties.get_bounds()),
+            min: properties.try_get_pair(PROPERTY_MIN_SIZE).unwrap_or((0, 0)),
+            max: properties
+                .try_get_pair(PROPERTY_MAX_SIZE)
+                .unwrap_or((u32::MAX, u32::MAX)),
+            stretch: properties.get_value(PROPERTY_STRETCH).max(0) as u32,
+            margin: properties.get_margin(),
+            cell: properties.try_get_grid_cell().ok(),
+        }
+    }
+
+    /// Limits a length along the given axis to the minimum and maximum sizes.  The minimum size
+    /// takes precedence if the two conflict.
+    fn clamp(&self, axis: Axis, length: i64) -> u32 {
+        let (min, max) = match axis {
+            Axis::Horizontal => (self.min.0, self.max.0),
+            Axis::Vertical => (self.min.1, self.max.1),
+        };
+
+        length.min(i64::from(max)).max(i64::from(min)) as u32
+    }
+
+    /// Returns the preferred length along the given axis, within the minimum and maximum sizes.
+    fn preferred(&self, axis: Axis) -> u32 {
+        match axis {
+            Axis::Horizontal => self.clamp(axis, i64::from(self.preferred.0)),
+            Axis::Vertical => self.clamp(axis, i64::from(self.preferred.1)),
+        }
+    }
+
+    /// Returns the margins before and after the `Widget` along the given axis.
+    fn margins(&self, axis: Axis) -> (i32, i32) {
+        match axis {
+            Axis::Horizontal => (self.margin.left, self.margin.right),
+            Axis::Vertical => (self.margin.top, self.margin.bottom),
+        }
+    }
+
+    /// Sizes and places the `Widget` along one axis, within a span that starts at `start` and has
+    /// the given `extent`.  Returns the position and length of the `Widget`.
+    fn place(&self, axis: Axis, alignment: Alignment, start: i32, extent: i32) -> (i32, u32) {
+        let (near, far) = self.margins(axis);
+        let available = extent - near - far;
+        let length = if alignment == Alignment::Stretch {
+            self.clamp(axis, i64::from(available.max(0)))
+        } else {
+            self.preferred(axis)
+        };
+        let offset = match alignment {
+            Alignment::Start | Alignment::Stretch => 0,
+            Alignment::Center => (available - length as i32) / 2,
+            Alignment::End => available - length as i32,
+        };
+
+        (start + near + offset, length)
+    }
+}
+
+/// Builds a `Rect` from positions and lengths along the main axis of a container, and across it.
+fn axis_rect(
+    axis: Axis,
+    (main, main_length): (i32, u32),
+    (cross, cross_length): (i32, u32),
+) -> Rect {
+    match axis {
+        Axis::Horizontal => Rect::new(main, cross, main_length, cross_length),
+        Axis::Vertical => Rect::new(cross, main, cross_length, main_length),
+    }
+}
+
+/// Divides an `amount` into shares, proportional to the given weights.  Any remainder from
+/// rounding is given to the last share, so that the shares add up to the `amount`.
+fn share(amount: i64, weights: &[u32]) -> Vec<i64> {
+    let total: i64 = weights.iter().map(|weight| i64::from(*weight)).sum();
+
+    if total == 0 {
+        return vec![0; weights.len()];
+    }
+
+    let mut shares: Vec<i64> = weights
+        .iter()
+        .map(|weight| amount * i64::from(*weight) / total)
+        .collect();
+    let given: i64 = shares.iter().sum();
+
+    if let Some(last) = weights.iter().rposition(|weight| *weight > 0) {
+        shares[last] += amount - given;
+    }
+
+    shares
+}
+
+/// Places the children of a box container in a row (`Axis::Horizontal`) or a column
+/// (`Axis::Vertical`) within `area`, separated by `spacing`.  Children are given their preferred
+/// length along the axis; any space left over is shared between children with a stretch factor,
+/// and if there is not enough space, those children are shrunk, within their minimum and maximum
+/// sizes.  Across the axis, children are placed according to the `alignment`.
+pub fn arrange_box(
+    axis: Axis,
+    area: Rect,
+    spacing: i32,
+    alignment: Alignment,
+    items: &[LayoutItem],
+) -> Vec<Rect> {
+    if items.is_empty() {
+        return Vec::new();
+    }
+
+    let (start, extent, cross_start, cross_extent) = match axis {
+        Axis::Horizontal => (area.x(), area.width(), area.y(), area.height()),
+        Axis::Vertical => (area.y(), area.height(), area.x(), area.width()),
+    };
+    let cross_axis = match axis {
+        Axis::Horizontal => Axis::Vertical,
+        Axis::Vertical => Axis::Horizontal,
+    };
+    let mut lengths: Vec<u32> = items.iter().map(|item| item.preferred(axis)).collect();
+    let used: i64 = items
+        .iter()
+        .zip(&lengths)
+        .map(|(item, length)| {
+            let (near, far) = item.margins(axis);
+
+            i64::from(*length) + i64::from(near) + i64::from(far)
+        })
+        .sum::<i64>()
+        + i64::from(spacing) * (items.len() as i64 - 1);
+    let mut remaining = i64::from(extent) - used;
+    let mut active: Vec<usize> = (0..items.len())
+        .filter(|index| items[*index].stretch > 0)
+        .collect();
+
+    // Children that reach their minimum or maximum size stop taking part, and the rest of the
+    // space is shared again between the others.
+    while remaining != 0 && !active.is_empty() {
+        let weights: Vec<u32> = active.iter().map(|index| items[*index].stretch).collect();
+        let shares = share(remaining, &weights);
+        let mut limited = Vec::new();
+
+        for (index, amount) in active.iter().zip(shares) {
+            let target = i64::from(lengths[*index]) + amount;
+            let length = items[*index].clamp(axis, target.max(0));
+
+            remaining -= i64::from(length) - i64::from(lengths[*index]);
+            lengths[*index] = length;
+
+            if i64::from(length) != target {
+                limited.push(*index);
+            }
+        }
+
+        if limited.is_empty() {
+            break;
+        }
+
+        active.retain(|index| !limited.contains(index));
+    }
+
+    let mut position = start;
+
+    items
+        .iter()
+        .zip(lengths)
+        .map(|(item, length)| {
+            let (near, far) = item.margins(axis);
+            let main = (position + near, length);
+
+            position += near + length as i32 + far + spacing;
+
+            axis_rect(
+                axis,
+                main,
+                item.place(cross_axis, alignment, cross_start, cross_extent as i32),
+            )
+        })
+        .collect()
+}
+
+/// Limits a cell to the given number of columns, and to `GRID_TRACK_LIMIT` rows.  A cell that
+/// starts beyond the last column or row is moved into it, and spans are shortened to end at the
+/// last column or row.
+fn clamp_cell((row, column, row_span, column_span): GridCell, columns: u32) -> GridCell {
+    let row = row.min(GRID_TRACK_LIMIT - 1);
+    let column = column.min(columns - 1);
+
+    (
+        row,
+        column,
+        row_span.clamp(1, GRID_TRACK_LIMIT - row),
+        column_span.clamp(1, columns - column),
+    )
+}
+
+/// Assigns the children of a grid container to cells, and sizes the columns and rows of the grid
+/// to fit them within the given extents.  Returns the cell of each child, then the width of each
+/// column, and the height of each row.  The number of columns is limited to `GRID_TRACK_LIMIT`,
+/// and cells are limited to the columns with `clamp_cell`.
+fn grid_tracks(
+    extents: Size,
+    columns: u32,
+    spacing: i32,
+    items: &[LayoutItem],
+) -> (Vec<GridCell>, Vec<i64>, Vec<i64>) {
+    let columns = columns.clamp(1, GRID_TRACK_LIMIT);
+    let mut occupied: HashSet<(u32, u32)> = HashSet::new();
+    let mut cells: Vec<Option<GridCell>> = items
+        .iter()
+        .map(|item| item.cell.map(|cell| clamp_cell(cell, columns)))
+        .collect();
+
+    for (row, column, row_span, column_span) in cells.iter().flatten() {
+        for spanned_row in *row..row + row_span {
+            for spanned_column in *column..column + column_span {
+                occupied.insert((spanned_row, spanned_column));
+            }
+        }
+    }
+
+    let mut next = 0;
+
+    for cell in cells.iter_mut().filter(|cell| cell.is_none()) {
+        while occupied.contains(&(next / columns, next % columns)) {
+            next += 1;
+        }
+
+        occupied.insert((next / columns, next % columns));
+        *cell = Some((next / columns, next % columns, 1, 1));
+    }
+
+    let cells: Vec<GridCell> = cells.into_iter().flatten().collect();
+    let rows = cells
+        .iter()
+        .map(|(row, _, row_span, _)| row + row_span)
+        .max()
+        .unwrap_or(0);
+    let tracks = |axis: Axis, count: u32, extent: u32| -> Vec<i64> {
+        let span_of = |cell: &GridCell| match axis {
+            Axis::Horizontal => (cell.1, cell.3),
+            Axis::Vertical => (cell.0, cell.2),
+        };
+        let mut sizes = vec![0_i64; count as usize];
+        let mut stretches = vec![0_u32; count as usize];
+        let mut spanning: Vec<(usize, &LayoutItem)> = (0..items.len())
+            .map(|index| (index, &items[index]))
+            .collect();
+
+        // Children spanning a single cell size their track first, then wider children grow the
+        // tracks they span if they do not fit.
+        spanning.sort_by_key(|(index, _)| span_of(&cells[*index]).1);
+
+        for (index, item) in spanning {
+            let (first, span) = span_of(&cells[index]);
+            let (near, far) = item.margins(axis);
+            let needed = i64::from(item.preferred(axis)) + i64::from(near) + i64::from(far);
+            let spanned = first as usize..(first + span) as usize;
+            let current: i64 = sizes[spanned.clone()].iter().sum::<i64>()
+                + i64::from(spacing) * (i64::from(span) - 1);
+
+            if needed > current {
+                let extra = share(needed - current, &vec![1; span as usize]);
+
+                for (size, extra) in sizes[spanned.clone()].iter_mut().zip(extra) {
+                    *size += extra;
+                }
+            }
+
+            for stretch in stretches[spanned].iter_mut() {
+                *stretch = (*stretch).max(item.stretch);
+            }
+        }
+
+        let used = sizes.iter().sum::<i64>() + i64::from(spacing) * (i64::from(count) - 1).max(0);
+        let remaining = i64::from(extent) - used;
+
+        if remaining > 0 {
+            for (size, extra) in sizes.iter_mut().zip(share(remaining, &stretches)) {
+                *size += extra;
+            }
+        }
+
+        sizes
+    };
+
+    let widths = tracks(Axis::Horizontal, columns, extents.0);
+    let heights = tracks(Axis::Vertical, rows, extents.1);
+
+    (cells, widths, heights)
+}
+
+/// Places the children of a grid container within `area`.  Children with a cell are placed in
+/// it, and the others fill the free cells in order, row by row, with the given number of
+/// `columns`.  Each column is as wide as its widest child, and each row is as tall as its tallest
+/// child; a child spanning several cells widens the cells it spans if it does not fit in them.
+/// Any space left over is shared between the columns and rows that contain a child with a
+/// stretch factor.  Within its cells, each child is placed according to the `alignment`.
+pub fn arrange_grid(
+    area: Rect,
+    columns: u32,
+    spacing: i32,
+    alignment: Alignment,
+    items: &[LayoutItem],
+) -> Vec<Rect> {
+    let (cells, widths, heights) =
+        grid_tracks((area.width(), area.height()), columns, spacing, items);
+    let span = |sizes: &[i64], start: i32, first: u32, count: u32| {
+        let position =
+            start + sizes[..first as usize].iter().sum::<i64>() as i32 + spacing * first as i32;
+        let length = sizes[first as usize..(first + count) as usize]
+            .iter()
+            .sum::<i64>() as i32
+            + spacing * (count as i32 - 1);
+
+        (position, length)
+    };
+
+    items
+        .iter()
+        .zip(cells)
+        .map(|(item, (row, column, row_span, column_span))| {
+            let (x, width) = span(&widths, area.x(), column, column_span);
+            let (y, height) = span(&heights, area.y(), row, row_span);
+
+            axis_rect(
+                Axis::Horizontal,
+                item.place(Axis::Horizontal, alignment, x, width),
+                item.place(Axis::Vertical, alignment, y, height),
+            )
+        })
+        .collect()
+}
+
+/// Places the children of a stack container on top of each other, each within the whole `area`,
+/// according to the `alignment`.
+pub fn arrange_stack(area: Rect, alignment: Alignment, items: &[LayoutItem]) -> Vec<Rect> {
+    items
+        .iter()
+        .map(|item| {
+            axis_rect(
+                Axis::Horizontal,
+                item.place(Axis::Horizontal, alignment, area.x(), area.width() as i32),
+                item.place(Axis::Vertical, alignment, area.y(), area.height() as i32),
+            )
+        })
+        .collect()
+}
+
+/// Measures the size a box container needs to fit its children at their preferred sizes, not
+/// including its own border and padding.
+pub fn measure_box(axis: Axis, spacing: i32, items: &[LayoutItem]) -> Size {
+    let outer = |item: &LayoutItem, axis: Axis| {
+        let (near, far) = item.margins(axis);
+
+        (i64::from(item.preferred(axis)) + i64::from(near) + i64::from(far)).max(0)
+    };
+    let cross_axis = match axis {
+        Axis::Horizontal => Axis::Vertical,
+        Axis::Vertical => Axis::Horizontal,
+    };
+    let main = items.iter().map(|item| outer(item, axis)).sum::<i64>()
+        + i64::from(spacing) * (items.len() as i64 - 1).max(0);
+    let cross = items
+        .iter()
+        .map(|item| outer(item, cross_axis))
+        .max()
+        .unwrap_or(0);
+
+    match axis {
+        Axis::Horizontal => (main as u32, cross as u32),
+        Axis::Vertical => (cross as u32, main as u32),
+    }
+}
+
+/// Measures the size a grid container needs to fit its children at their preferred sizes, not
+/// including its own border and padding.
+pub fn measure_grid(columns: u32, spacing: i32, items: &[LayoutItem]) -> Size {
+    let (_, widths, heights) = grid_tracks((0, 0), columns, spacing, items);
+    let total = |sizes: &[i64]| {
+        (sizes.iter().sum::<i64>() + i64::from(spacing) * (sizes.len() as i64 - 1).max(0)).max(0)
+            as u32
+    };
+
+    (total(&widths), total(&heights))
+}
+
+/// Measures the size a stack container needs to fit each of its children at their preferred
+/// sizes, not including its own border and padding.
+pub fn measure_stack(items: &[LayoutItem]) -> Size {
+    items.iter().fold((0, 0), |(width, height), item| {
+        let (left, right) = item.margins(Axis::Horizontal);
+        let (top, bottom) = item.margins(Axis::Vertical);
+
+        (
+            width.max((item.preferred(Axis::Horizontal) as i32 + left + right).max(0) as u32),
+            height.max((item.preferred(Axis::Vertical) as i32 + top + bottom).max(0) as u32),
+        )
+    })
+}
+
+/// Returns the size a container `Widget` needs: its `PROPERTY_PREFERRED_SIZE`, if set, or
+/// otherwise the size its children need, measured by `measure_box`, `measure_grid` or
+/// `measure_stack`, with its border and padding added.
+pub fn container_size(properties: &WidgetProperties, content: Size) -> Size {
+    properties
+        .try_get_pair(PROPERTY_PREFERRED_SIZE)
+        .unwrap_or_else(|_| {
+            let insets = properties.get_insets();
+
+            (
+                (content.0 as i32 + insets.left + insets.right).max(0) as u32,
+                (content.1 as i32 + insets.top + insets.bottom).max(0) as u32,
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(cell: Option<GridCell>) -> LayoutItem {
+        LayoutItem {
+            preferred: (10, 10),
+            min: (0, 0),
+            max: (u32::MAX, u32::MAX),
+            stretch: 0,
+            margin: Sides::uniform(0),
+            cell,
+        }
+    }
+
+    #[test]
+    fn grid_tracks_places_spanning_cells() {
+        let items = [item(Some((0, 0, 2, 2))), item(None), item(None), item(None)];
+        let (cells, widths, heights) = grid_tracks((0, 0), 3, 0, &items);
+
+        assert_eq!(
+            cells,
+            vec![(0, 0, 2, 2), (0, 2, 1, 1), (1, 2, 1, 1), (2, 0, 1, 1)]
+        );
+        assert_eq!(widths, vec![10, 0, 10]);
+        assert_eq!(heights, vec![10, 10, 10]);
+    }
+
+    #[test]
+    fn grid_tracks_clamps_cells_to_the_columns() {
+        let items = [item(Some((0, 5, 1, 1))), item(Some((1, 1, 1, 9)))];
+        let (cells, widths, _) = grid_tracks((0, 0), 3, 0, &items);
+
+        assert_eq!(cells, vec![(0, 2, 1, 1), (1, 1, 1, 2)]);
+        assert_eq!(widths.len(), 3);
+    }
+
+    #[test]
+    fn grid_tracks_does_not_overflow_on_huge_cells() {
+        let items = [item(Some((u32::MAX, u32::MAX, u32::MAX, u32::MAX)))];
+        let (cells, widths, heights) = grid_tracks((0, 0), u32::MAX, 0, &items);
+
+        assert_eq!(
+            cells,
+            vec![(GRID_TRACK_LIMIT - 1, GRID_TRACK_LIMIT - 1, 1, 1)]
+        );
+        assert_eq!(widths.len(), GRID_TRACK_LIMIT as usize);
+        assert_eq!(heights.len(), GRID_TRACK_LIMIT as usize);
+    }
+
+    #[test]
+    fn arrange_grid_sizes_spans_with_spacing() {
+        let items = [item(Some((0, 0, 1, 2))), item(None), item(None)];
+        let rects = arrange_grid(Rect::new(0, 0, 25, 25), 2, 5, Alignment::Stretch, &items);
+
+        assert_eq!(
+            rects,
+            vec![
+                Rect::new(0, 0, 25, 10),
+                Rect::new(0, 15, 10, 10),
+                Rect::new(15, 15, 10, 10),
+            ]
+        );
+    }
+
+    fn anchors(
+        top: Option<Length>,
+        right: Option<Length>,
+        bottom: Option<Length>,
+        left: Option<Length>,
+    ) -> Sides<Option<Length>> {
+        Sides {
+            top,
+            right,
+            bottom,
+            left,
+        }
+    }
+
+    #[test]
+    fn placement_stretches_between_both_anchors() {
+        let placement = Placement {
+            size: (Some(Length::Pixels(30)), None),
+            origin: (None, None),
+            anchors: anchors(
+                None,
+                Some(Length::Pixels(20)),
+                None,
+                Some(Length::Pixels(10)),
+            ),
+        };
+        let margin = Sides {
+            top: 0,
+            right: 2,
+            bottom: 0,
+            left: 1,
+        };
+
+        assert_eq!(
+            placement.resolve(Rect::new(5, 0, 100, 50), margin, (3, 4), (7, 8)),
+            ((16, 4), (67, 8))
+        );
+    }
+
+    #[test]
+    fn placement_keeps_a_percent_size_from_one_anchor() {
+        let placement = Placement {
+            size: (Some(Length::Percent(50.0)), Some(Length::Percent(25.0))),
+            origin: (None, None),
+            anchors: anchors(
+                None,
+                Some(Length::Pixels(10)),
+                Some(Length::Pixels(5)),
+                None,
+            ),
+        };
+        let margin = Sides {
+            top: 0,
+            right: 0,
+            bottom: 3,
+            left: 0,
+        };
+
+        assert_eq!(
+            placement.resolve(Rect::new(0, 0, 200, 100), margin, (3, 4), (7, 8)),
+            ((90, 67), (100, 25))
+        );
+    }
+
+    #[test]
+    fn placement_adds_the_margin_to_a_relative_origin() {
+        let placement = Placement {
+            size: (None, None),
+            origin: (Some(Length::Percent(50.0)), Some(Length::Pixels(5))),
+            anchors: Sides::uniform(None),
+        };
+        let margin = Sides {
+            top: 3,
+            right: 0,
+            bottom: 0,
+            left: 2,
+        };
+
+        assert_eq!(
+            placement.resolve(Rect::new(10, 20, 100, 100), margin, (3, 4), (7, 8)),
+            ((62, 28), (7, 8))
+        );
+    }
+
+    #[test]
+    fn lengths_reads_auto_as_unset() {
+        assert_eq!(
+            lengths(&PropertyValue::String(String::from("10 auto 50% 20px"))),
+            Some(vec![
+                Some(Length::Pixels(10)),
+                None,
+                Some(Length::Percent(50.0)),
+                Some(Length::Pixels(20)),
+            ])
+        );
+        assert_eq!(
+            lengths(&PropertyValue::List(vec![
+                PropertyValue::String(String::from("AUTO")),
+                PropertyValue::Integer(4),
+            ])),
+            Some(vec![None, Some(Length::Pixels(4))])
+        );
+        assert_eq!(
+            lengths(&PropertyValue::String(String::from("10 wide"))),
+            None
+        );
+    }
+
+    #[test]
+    fn placement_reads_anchor_shorthands() {
+        let placement_with = |anchors: &str| {
+            let mut properties = WidgetProperties::default();
+
+            properties.set_string(PROPERTY_ANCHORS, String::from(anchors));
+            Placement::from_properties(&properties).unwrap().anchors
+        };
+        let pixels = |value: i32| Some(Length::Pixels(value));
+
+        assert_eq!(placement_with("5"), Sides::uniform(pixels(5)));
+        assert_eq!(
+            placement_with("5 auto"),
+            anchors(pixels(5), None, pixels(5), None)
+        );
+        assert_eq!(
+            placement_with("1 2 auto 4"),
+            anchors(pixels(1), pixels(2), None, pixels(4))
+        );
+        assert_eq!(placement_with("1 2 3"), Sides::uniform(None));
+    }
+
+    #[test]
+    fn placement_reads_sizes_and_origins() {
+        let mut properties = WidgetProperties::default();
+
+        assert_eq!(Placement::from_properties(&properties), None);
+
+        properties.set_string(PROPERTY_RELATIVE_SIZE, String::from("50%"));
+        properties.set_string(PROPERTY_RELATIVE_ORIGIN, String::from("10 25%"));
+
+        let placement = Placement::from_properties(&properties).unwrap();
+
+        assert_eq!(
+            placement.size,
+            (Some(Length::Percent(50.0)), Some(Length::Percent(50.0)))
+        );
+        assert_eq!(
+            placement.origin,
+            (Some(Length::Pixels(10)), Some(Length::Percent(25.0)))
+        );
+        assert_eq!(placement.anchors, Sides::uniform(None));
+    }
+
+    fn stretched(preferred: u32, stretch: u32) -> LayoutItem {
+        LayoutItem {
+            preferred: (preferred, 10),
+            stretch,
+            ..item(None)
+        }
+    }
+
+    fn widths(rects: &[Rect]) -> Vec<(i32, u32)> {
+        rects.iter().map(|rect| (rect.x(), rect.width())).collect()
+    }
+
+    #[test]
+    fn arrange_box_shares_space_by_stretch() {
+        let items = [stretched(10, 1), stretched(10, 0), stretched(10, 2)];
+        let rects = arrange_box(
+            Axis::Horizontal,
+            Rect::new(0, 0, 100, 20),
+            0,
+            Alignment::Stretch,
+            &items,
+        );
+
+        assert_eq!(widths(&rects), vec![(0, 33), (33, 10), (43, 57)]);
+        assert!(rects.iter().all(|rect| rect.height() == 20));
+    }
+
+    #[test]
+    fn arrange_box_shrinks_stretched_items_when_over_full() {
+        let items = [stretched(15, 1), stretched(15, 1)];
+        let rects = arrange_box(
+            Axis::Horizontal,
+            Rect::new(0, 0, 20, 20),
+            0,
+            Alignment::Stretch,
+            &items,
+        );
+
+        assert_eq!(widths(&rects), vec![(0, 10), (10, 10)]);
+    }
+
+    #[test]
+    fn arrange_box_shares_again_when_items_reach_their_limits() {
+        let limited = LayoutItem {
+            max: (20, u32::MAX),
+            ..stretched(10, 1)
+        };
+        let rects = arrange_box(
+            Axis::Horizontal,
+            Rect::new(0, 0, 100, 20),
+            0,
+            Alignment::Stretch,
+            &[limited, stretched(10, 1)],
+        );
+
+        assert_eq!(widths(&rects), vec![(0, 20), (20, 80)]);
+
+        let limited = LayoutItem {
+            min: (15, 0),
+            ..stretched(20, 1)
+        };
+        let rects = arrange_box(
+            Axis::Horizontal,
+            Rect::new(0, 0, 20, 20),
+            0,
+            Alignment::Stretch,
+            &[limited, stretched(20, 1)],
+        );
+
+        assert_eq!(widths(&rects), vec![(0, 15), (15, 5)]);
+    }
+
+    #[test]
+    fn arrange_box_aligns_items_across_the_axis() {
+        let arrange = |alignment: Alignment, item: LayoutItem| {
+            arrange_box(
+                Axis::Vertical,
+                Rect::new(0, 0, 40, 100),
+                5,
+                alignment,
+                &[item, item],
+            )
+        };
+
+        assert_eq!(
+            arrange(Alignment::Start, item(None)),
+            vec![Rect::new(0, 0, 10, 10), Rect::new(0, 15, 10, 10)]
+        );
+        assert_eq!(
+            arrange(Alignment::Center, item(None))[0],
+            Rect::new(15, 0, 10, 10)
+        );
+        assert_eq!(
+            arrange(Alignment::End, item(None))[0],
+            Rect::new(30, 0, 10, 10)
+        );
+        assert_eq!(
+            arrange(Alignment::Stretch, item(None))[0],
+            Rect::new(0, 0, 40, 10)
+        );
+
+        let margined = LayoutItem {
+            margin: Sides {
+                top: 0,
+                right: 4,
+                bottom: 0,
+                left: 2,
+            },
+            ..item(None)
+        };
+
+        assert_eq!(
+            arrange(Alignment::Stretch, margined)[0],
+            Rect::new(2, 0, 34, 10)
+        );
+    }
+}
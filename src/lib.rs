@@ -16,12 +16,24 @@
 /// store the properties.  Each property is identified by a numeric (u32) key.
 pub mod properties;
 
+/// This is the registry of property keys, which maps each key to a unique name, the type of value
+/// it stores, its default value, and what needs to happen to a `Widget` when it changes.  Custom
+/// `Widget`s register their own keys here, rather than choosing numbers that may collide.
+pub mod property_registry;
+
+/// Parsing of colors from hexadecimal, `rgb()`, `hsl()` and named color text, used when color
+/// properties are stored as text.
+pub mod colors;
+
 /// This is the `Widget` trait that all drawable `Widget`s use.  Any special functionality should
 /// be defined using interactions with properties.  `Widget`s can decide whether or not to set
 /// themselves in `invalidated` state after a property value changes, which indicates to the
 /// top-level drawing loop whether or not a `Widget` needs to be redrawn.
 pub mod widget;
 
+/// Input events sent to `Widget`s, converted from the events generated by SDL2.
+pub mod event;
+
 /// This is a `Widget` and `Texture` cache that are used by `Widget`s.
 pub mod caches;
 
@@ -29,5 +41,44 @@ pub mod caches;
 /// `Texture` stored within is used for blitting to the screen.
 pub mod texture_store;
 
+/// This is a `Theme`, which maps selectors of `Widget`s to sets of properties, and is applied
+/// to `Widget`s by the `WidgetCache`.
+pub mod theme;
+
+/// Animation of `Widget` properties over time, with easing curves, delays and repeats, driven by
+/// `WidgetCache::tick`.
+pub mod animation;
+
+/// Undo and redo history of changes made to a `WidgetCache`.
+pub mod history;
+
+/// Borders with per-side widths and colors, rounded corners and styles, computed as rectangles
+/// that `Widget`s fill when they draw.
+pub mod border;
+
+/// Gradient and image backgrounds, painted behind the border of a `Widget`.
+pub mod background;
+
+/// Drop shadows and outer glows, generated in software and drawn beneath a `Widget` by the
+/// `WidgetCache`.
+pub mod shadow;
+
+/// Placement of `Widget`s relative to the content area of their parent, using percentages and
+/// anchors, resolved by the `WidgetCache` when the parent's bounds change.
+pub mod layout;
+
 /// System-provided Widget library.
 pub mod system_widgets;
+
+/// This is a registry that maps `Widget` type names to constructors, used to create `Widget`s
+/// by name when a scene is loaded or a `WidgetCache` is imported.
+pub mod widget_registry;
+
+/// Export and import of a `WidgetCache` as JSON or RON, available with the `serde` feature.
+#[cfg(feature = "serde")]
+pub mod serialization;
+
+/// Loader for scene files that describe a tree of `Widget`s in JSON, RON or TOML, available with
+/// the `serde` feature.
+#[cfg(feature = "serde")]
+pub mod scene;
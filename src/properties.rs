@@ -13,9 +13,19 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::colors::{parse_color, ColorError};
+use crate::layout::GRID_TRACK_LIMIT;
+use crate::property_registry::{default_of, invalidation_of, property_name, Invalidation};
 use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use std::borrow::Cow;
 use std::collections::HashMap;
-use std::u32;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
 
 pub const PROPERTY_NATIVE_WIDGET_ADDER: u32 = 0;
 pub const PROPERTY_INVALIDATED: u32 = 1;
@@ -26,12 +36,370 @@ pub const PROPERTY_TEXT: u32 = 5;
 pub const PROPERTY_MAIN_COLOR: u32 = 6;
 pub const PROPERTY_BORDER_COLOR: u32 = 7;
 pub const PROPERTY_BORDER_WIDTH: u32 = 8;
+pub const PROPERTY_FONT_NAME: u32 = 9;
+pub const PROPERTY_FONT_SIZE: u32 = 10;
+pub const PROPERTY_PADDING: u32 = 11;
+pub const PROPERTY_HOVERED: u32 = 12;
+pub const PROPERTY_DISABLED: u32 = 13;
+pub const PROPERTY_TEXT_COLOR: u32 = 14;
+pub const PROPERTY_OPACITY: u32 = 15;
+pub const PROPERTY_BORDER_WIDTHS: u32 = 16;
+pub const PROPERTY_BORDER_COLORS: u32 = 17;
+pub const PROPERTY_BORDER_RADIUS: u32 = 18;
+pub const PROPERTY_BORDER_STYLE: u32 = 19;
+pub const PROPERTY_BACKGROUND_GRADIENT: u32 = 20;
+pub const PROPERTY_BACKGROUND_IMAGE: u32 = 21;
+pub const PROPERTY_BACKGROUND_IMAGE_MODE: u32 = 22;
+pub const PROPERTY_SHADOW_OFFSET: u32 = 23;
+pub const PROPERTY_SHADOW_BLUR: u32 = 24;
+pub const PROPERTY_SHADOW_SPREAD: u32 = 25;
+pub const PROPERTY_SHADOW_COLOR: u32 = 26;
+pub const PROPERTY_MARGIN: u32 = 27;
+pub const PROPERTY_RELATIVE_SIZE: u32 = 28;
+pub const PROPERTY_RELATIVE_ORIGIN: u32 = 29;
+pub const PROPERTY_ANCHORS: u32 = 30;
+pub const PROPERTY_SPACING: u32 = 31;
+pub const PROPERTY_ALIGNMENT: u32 = 32;
+pub const PROPERTY_GRID_COLUMNS: u32 = 33;
+pub const PROPERTY_STRETCH: u32 = 34;
+pub const PROPERTY_MIN_SIZE: u32 = 35;
+pub const PROPERTY_MAX_SIZE: u32 = 36;
+pub const PROPERTY_GRID_CELL: u32 = 37;
+pub const PROPERTY_PREFERRED_SIZE: u32 = 38;
+pub const PROPERTY_HOVER_COLOR: u32 = 39;
+pub const PROPERTY_DRAG_THRESHOLD: u32 = 40;
+pub const PROPERTY_FOCUSABLE: u32 = 41;
+pub const PROPERTY_TAB_INDEX: u32 = 42;
+pub const PROPERTY_FOCUSED: u32 = 43;
+pub const PROPERTY_FOCUS_RING_COLOR: u32 = 44;
+pub const PROPERTY_FOCUS_RING_WIDTH: u32 = 45;
+
+/// Returns whether or not a property holds transient state that is managed for a `Widget` while
+/// it is running - whether it needs to be redrawn, is under the pointer, or has the keyboard
+/// focus.  Transient properties are never supplied by a `Theme`, nor saved when a `WidgetCache`
+/// is exported.
+pub fn is_transient(property_key: u32) -> bool {
+    matches!(
+        property_key,
+        PROPERTY_INVALIDATED | PROPERTY_HOVERED | PROPERTY_FOCUSED
+    )
+}
+
+/// This is a typed value stored for a property.  Values are stored in their native form, so no
+/// parsing is required when a `Widget` reads them back during a draw cycle.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PropertyValue {
+    /// A boolean flag.
+    Bool(bool),
+
+    /// A signed numeric value.
+    Integer(i32),
+
+    /// A pair of unsigned values, used for sizes.
+    Pair(u32, u32),
+
+    /// A pair of signed coordinates, used for origins.
+    Point(i32, i32),
+
+    /// An RGBA color.
+    #[cfg_attr(feature = "serde", serde(with = "color_format"))]
+    Color(Color),
+
+    /// A text value.
+    String(String),
+
+    /// A rectangle, stored as an `SDL2` `Rect`.
+    #[cfg_attr(feature = "serde", serde(with = "rect_format"))]
+    Rect(Rect),
+
+    /// A list of values.
+    List(Vec<PropertyValue>),
+}
+
+/// Serializes an `SDL2` `Color` as an `[r, g, b, a]` sequence.
+#[cfg(feature = "serde")]
+mod color_format {
+    use sdl2::pixels::Color;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
+        [color.r, color.g, color.b, color.a].serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        let [r, g, b, a] = <[u8; 4]>::deserialize(deserializer)?;
+
+        Ok(Color::RGBA(r, g, b, a))
+    }
+}
+
+/// Serializes an `SDL2` `Rect` as an `[x, y, w, h]` sequence.
+#[cfg(feature = "serde")]
+mod rect_format {
+    use sdl2::rect::Rect;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(rect: &Rect, serializer: S) -> Result<S::Ok, S::Error> {
+        (rect.x(), rect.y(), rect.width(), rect.height()).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Rect, D::Error> {
+        let (x, y, w, h) = <(i32, i32, u32, u32)>::deserialize(deserializer)?;
+
+        Ok(Rect::new(x, y, w, h))
+    }
+}
+
+impl From<bool> for PropertyValue {
+    fn from(value: bool) -> Self {
+        PropertyValue::Bool(value)
+    }
+}
+
+impl From<i32> for PropertyValue {
+    fn from(value: i32) -> Self {
+        PropertyValue::Integer(value)
+    }
+}
+
+impl From<(u32, u32)> for PropertyValue {
+    fn from(value: (u32, u32)) -> Self {
+        PropertyValue::Pair(value.0, value.1)
+    }
+}
+
+impl From<(i32, i32)> for PropertyValue {
+    fn from(value: (i32, i32)) -> Self {
+        PropertyValue::Point(value.0, value.1)
+    }
+}
+
+impl From<Color> for PropertyValue {
+    fn from(value: Color) -> Self {
+        PropertyValue::Color(value)
+    }
+}
+
+impl From<String> for PropertyValue {
+    fn from(value: String) -> Self {
+        PropertyValue::String(value)
+    }
+}
+
+impl From<&str> for PropertyValue {
+    fn from(value: &str) -> Self {
+        PropertyValue::String(String::from(value))
+    }
+}
+
+impl From<Rect> for PropertyValue {
+    fn from(value: Rect) -> Self {
+        PropertyValue::Rect(value)
+    }
+}
+
+impl From<Vec<PropertyValue>> for PropertyValue {
+    fn from(value: Vec<PropertyValue>) -> Self {
+        PropertyValue::List(value)
+    }
+}
+
+/// Shrinks a rectangle by the given amount on each side (growing it for negative amounts),
+/// returning `None` if nothing is left of it.
+fn inset_rect(x: i32, y: i32, width: i32, height: i32, insets: Sides<i32>) -> Option<Rect> {
+    let width = width - insets.left - insets.right;
+    let height = height - insets.top - insets.bottom;
+
+    if width <= 0 || height <= 0 {
+        None
+    } else {
+        Some(Rect::new(
+            x + insets.left,
+            y + insets.top,
+            width as u32,
+            height as u32,
+        ))
+    }
+}
+
+/// This is a set of values for each side of a `Widget`, such as the widths of its border.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sides<T> {
+    pub top: T,
+    pub right: T,
+    pub bottom: T,
+    pub left: T,
+}
+
+/// This is the implementation of `Sides`.
+impl<T: Copy> Sides<T> {
+    /// Creates a set of `Sides` with the same value on every side.
+    pub fn uniform(value: T) -> Self {
+        Self {
+            top: value,
+            right: value,
+            bottom: value,
+            left: value,
+        }
+    }
+
+    /// Creates a set of `Sides` from 1, 2 or 4 values, following the CSS shorthand order: a
+    /// single value applies to every side, two values apply to the top and bottom, then left and
+    /// right, and four values apply to the top, right, bottom and left sides.  Returns `None` for
+    /// any other number of values.
+    pub fn from_shorthand(values: &[T]) -> Option<Self> {
+        match *values {
+            [all] => Some(Self::uniform(all)),
+            [vertical, horizontal] => Some(Self {
+                top: vertical,
+                right: horizontal,
+                bottom: vertical,
+                left: horizontal,
+            }),
+            [top, right, bottom, left] => Some(Self {
+                top,
+                right,
+                bottom,
+                left,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// This is an error that is returned when a property cannot be retrieved as the requested type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyError {
+    /// The property has not been set.
+    MissingKey(u32),
+
+    /// The property is stored as a value that cannot be converted to the requested type.
+    WrongType { key: u32, expected: &'static str },
+
+    /// The property text contains the wrong number of components.
+    WrongArity {
+        key: u32,
+        expected: usize,
+        found: usize,
+    },
+
+    /// A component of the property text could not be parsed as a number.
+    ParseFailure { key: u32, value: String },
+
+    /// A component of the property text was parsed, but falls outside of the allowed range.
+    OutOfRange { key: u32, value: String },
+}
+
+impl fmt::Display for PropertyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PropertyError::MissingKey(key) => write!(f, "property {} has not been set", key),
+            PropertyError::WrongType { key, expected } => {
+                write!(f, "property {} cannot be read as {}", key, expected)
+            }
+            PropertyError::WrongArity {
+                key,
+                expected,
+                found,
+            } => write!(
+                f,
+                "property {} expected {} components, found {}",
+                key, expected, found
+            ),
+            PropertyError::ParseFailure { key, value } => {
+                write!(f, "property {} has an unparseable value '{}'", key, value)
+            }
+            PropertyError::OutOfRange { key, value } => {
+                write!(f, "property {} has an out of range value '{}'", key, value)
+            }
+        }
+    }
+}
+
+impl Error for PropertyError {}
+
+/// This is a change to a single property, as applied by a `PropertyTransaction`.  A value of
+/// `None` indicates that the property was not set before the change, or has been deleted by it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangedProperty {
+    pub property_key: u32,
+    pub old_value: Option<PropertyValue>,
+    pub new_value: Option<PropertyValue>,
+}
+
+/// This is a group of property changes that are applied to a `WidgetProperties` store at once,
+/// through `WidgetProperties::apply`.  Changes are applied in the order in which they are made, so
+/// if a property is changed more than once, the last change is the one that is kept.
+#[derive(Debug, Clone, Default)]
+pub struct PropertyTransaction {
+    changes: Vec<(u32, Option<PropertyValue>)>,
+}
+
+/// This is the implementation of the `PropertyTransaction`, which mirrors the setters of the
+/// `WidgetProperties` store.  None of the changes take effect until the transaction is applied.
+impl PropertyTransaction {
+    /// Sets a value for a property based on its numerical key.
+    pub fn set(&mut self, property_key: u32, property_value: PropertyValue) -> &mut Self {
+        self.changes.push((property_key, Some(property_value)));
+        self
+    }
+
+    /// Deletes a property value for the given numerical key.
+    pub fn delete(&mut self, property_key: u32) -> &mut Self {
+        self.changes.push((property_key, None));
+        self
+    }
+
+    /// Stores the color for the specified key.
+    pub fn set_color(&mut self, property_key: u32, color: Color) -> &mut Self {
+        self.set(property_key, PropertyValue::Color(color))
+    }
+
+    /// Sets the size of the `Widget`.
+    pub fn set_bounds(&mut self, w: u32, h: u32) -> &mut Self {
+        self.set(PROPERTY_SIZE, PropertyValue::Pair(w, h))
+    }
+
+    /// Sets the origin for the `Widget`.
+    pub fn set_origin(&mut self, x: i32, y: i32) -> &mut Self {
+        self.set(PROPERTY_ORIGIN, PropertyValue::Point(x, y))
+    }
+
+    /// Sets a boolean for a given property key.
+    pub fn set_bool(&mut self, property_key: u32) -> &mut Self {
+        self.set(property_key, PropertyValue::Bool(true))
+    }
+
+    /// Sets a numeric value to a given property key.
+    pub fn set_value(&mut self, property_key: u32, value: i32) -> &mut Self {
+        self.set(property_key, PropertyValue::Integer(value))
+    }
+
+    /// Sets a text value to a given property key.
+    pub fn set_string(&mut self, property_key: u32, value: String) -> &mut Self {
+        self.set(property_key, PropertyValue::String(value))
+    }
+
+    /// Returns a flag indicating whether or not any changes have been made.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
 
 /// This is a structure that stores properties for Widgets, which can be used to define the object's
-/// behavior.
+/// behavior.  Values set on the `Widget` take precedence over values supplied by a `Theme`, which
+/// in turn take precedence over values inherited from the `Widget`'s parent.  `Theme` and
+/// inherited values are stored separately, so that they can be replaced when the `Theme` or the
+/// parent changes.
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct WidgetProperties {
-    properties: HashMap<u32, String>,
+    properties: HashMap<u32, PropertyValue>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    theme_properties: HashMap<u32, PropertyValue>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    inherited_properties: HashMap<u32, PropertyValue>,
 }
 
 /// This is the implementation of the `WidgetProperties` store.  This is used by each and every
@@ -41,22 +409,124 @@ impl WidgetProperties {
     /*
      * PRIVATE MEMBERS
      */
+    /// Retrieves the value for a property as `get` does, falling back to the default value the
+    /// property was registered with.  Returns `PropertyError::MissingKey` if the property has not
+    /// been set, and has no default.
     #[inline]
-    fn get_tuples(&self, property_key: u32, default_tuple: (u32, u32)) -> (u32, u32) {
-        if self.properties.contains_key(&property_key) {
-            let tokens: Vec<&str> = self
-                .properties
-                .get(&property_key)
-                .unwrap()
-                .split(' ')
-                .collect();
+    fn lookup(&self, property_key: u32) -> Result<Cow<'_, PropertyValue>, PropertyError> {
+        match self.get(property_key) {
+            Some(value) => Ok(Cow::Borrowed(value)),
+            None => default_of(property_key).ok_or(PropertyError::MissingKey(property_key)),
+        }
+    }
 
-            (
-                u32::from_str_radix(tokens[0], 10).unwrap(),
-                u32::from_str_radix(tokens[1], 10).unwrap(),
-            )
-        } else {
-            default_tuple
+    /// Splits a space-separated property text, or a list of numbers, into numeric components,
+    /// checking that the number of components and each of their values fall within the allowed
+    /// ranges.
+    fn parse_components(
+        property_key: u32,
+        value: &PropertyValue,
+        arity: &[usize],
+        min: i64,
+        max: i64,
+    ) -> Result<Vec<i64>, PropertyError> {
+        let tokens: Vec<String> = match value {
+            PropertyValue::String(text) => text.split_whitespace().map(String::from).collect(),
+            PropertyValue::List(items) => items
+                .iter()
+                .map(|item| match item {
+                    PropertyValue::Integer(number) => Ok(number.to_string()),
+                    _ => Err(PropertyError::WrongType {
+                        key: property_key,
+                        expected: "a list of numbers",
+                    }),
+                })
+                .collect::<Result<_, _>>()?,
+            _ => {
+                return Err(PropertyError::WrongType {
+                    key: property_key,
+                    expected: "text or a list of numbers",
+                })
+            }
+        };
+
+        if !arity.contains(&tokens.len()) {
+            return Err(PropertyError::WrongArity {
+                key: property_key,
+                expected: arity[0],
+                found: tokens.len(),
+            });
+        }
+
+        tokens
+            .iter()
+            .map(|token| {
+                let value = token
+                    .parse::<i64>()
+                    .map_err(|_| PropertyError::ParseFailure {
+                        key: property_key,
+                        value: token.to_string(),
+                    })?;
+
+                if value < min || value > max {
+                    Err(PropertyError::OutOfRange {
+                        key: property_key,
+                        value: token.to_string(),
+                    })
+                } else {
+                    Ok(value)
+                }
+            })
+            .collect()
+    }
+
+    /// Retrieves a pair of unsigned values, such as a size.  Pairs stored as text are parsed from
+    /// the "w h" format, and pairs stored as a list are read from 2 numbers.
+    #[inline]
+    pub fn try_get_pair(&self, property_key: u32) -> Result<(u32, u32), PropertyError> {
+        match self.lookup(property_key)?.as_ref() {
+            PropertyValue::Pair(x, y) => Ok((*x, *y)),
+            value @ PropertyValue::String(_) | value @ PropertyValue::List(_) => {
+                let values =
+                    Self::parse_components(property_key, value, &[2], 0, i64::from(u32::MAX))?;
+
+                Ok((values[0] as u32, values[1] as u32))
+            }
+            _ => Err(PropertyError::WrongType {
+                key: property_key,
+                expected: "a pair",
+            }),
+        }
+    }
+
+    /// Retrieves a pair of signed coordinates, such as an origin or an offset.  Points stored as
+    /// text are parsed from the "x y" format, and points stored as a list are read from 2 numbers.
+    #[inline]
+    pub fn try_get_point(&self, property_key: u32) -> Result<(i32, i32), PropertyError> {
+        match self.lookup(property_key)?.as_ref() {
+            PropertyValue::Point(x, y) => Ok((*x, *y)),
+            PropertyValue::Pair(x, y) => match (i32::try_from(*x), i32::try_from(*y)) {
+                (Ok(x), Ok(y)) => Ok((x, y)),
+                _ => Err(PropertyError::OutOfRange {
+                    key: property_key,
+                    value: format!("{} {}", x, y),
+                }),
+            },
+            value @ PropertyValue::String(_) | value @ PropertyValue::List(_) => {
+                let values = Self::parse_components(
+                    property_key,
+                    value,
+                    &[2],
+                    i64::from(i32::MIN),
+                    i64::from(i32::MAX),
+                )?;
+
+                Ok((values[0] as i32, values[1] as i32))
+            }
+            _ => Err(PropertyError::WrongType {
+                key: property_key,
+                expected: "a point",
+            }),
         }
     }
 
@@ -65,7 +535,7 @@ impl WidgetProperties {
      */
 
     /// Sets a value for a property based on its numerical key.
-    pub fn set(&mut self, property_key: u32, property_value: String) {
+    pub fn set(&mut self, property_key: u32, property_value: PropertyValue) {
         self.properties.insert(property_key, property_value);
     }
 
@@ -74,17 +544,121 @@ impl WidgetProperties {
         self.properties.remove(&property_key);
     }
 
-    /// Retrieves the value for a property.
-    pub fn get(&mut self, property_key: u32) -> String {
+    /// Retrieves the value for a property.  If the property has not been set on the `Widget`, the
+    /// value supplied by the `Theme` is returned, followed by the value inherited from the parent.
+    /// Returns `None` if none of these have been set.
+    pub fn get(&self, property_key: u32) -> Option<&PropertyValue> {
         self.properties
             .get(&property_key)
-            .unwrap_or(&String::from(""))
-            .clone()
+            .or_else(|| self.theme_properties.get(&property_key))
+            .or_else(|| self.inherited_properties.get(&property_key))
+    }
+
+    /// Retrieves the value for a property that has been set on the `Widget`, ignoring any value
+    /// supplied by the `Theme`.
+    pub fn get_local(&self, property_key: u32) -> Option<&PropertyValue> {
+        self.properties.get(&property_key)
+    }
+
+    /// Retrieves the value for a property as `get` does, falling back to the default value the
+    /// property was registered with in the property registry.
+    pub fn get_or_default(&self, property_key: u32) -> Option<PropertyValue> {
+        self.lookup(property_key).ok().map(Cow::into_owned)
     }
 
-    /// Returns a flag indicating whether or not a property for a numerical key has been set.
-    pub fn key_set(&mut self, property_key: u32) -> bool {
-        self.properties.contains_key(&property_key)
+    /// Returns a flag indicating whether or not a property for a numerical key has been set, either
+    /// on the `Widget`, by the `Theme`, or by inheritance.
+    pub fn key_set(&self, property_key: u32) -> bool {
+        self.get(property_key).is_some()
+    }
+
+    /// Returns an iterator over all of the properties set on the `Widget`, ignoring any values
+    /// supplied by the `Theme`.
+    pub fn iter(&self) -> impl Iterator<Item = (&u32, &PropertyValue)> {
+        self.properties.iter()
+    }
+
+    /// Applies all of the changes in a `PropertyTransaction` at once.  Returns the properties whose
+    /// values actually changed, comparing the value before the transaction to the value after
+    /// it, so a property that is set to its existing value is not reported.  The invalidate flag is
+    /// set once if any of the changed properties affects the drawing of the `Widget`, as described
+    /// by its `Invalidation` behavior in the property registry.
+    pub fn apply(&mut self, transaction: PropertyTransaction) -> Vec<ChangedProperty> {
+        let mut changed: Vec<ChangedProperty> = Vec::new();
+
+        for (property_key, new_value) in transaction.changes {
+            let old_value = match new_value {
+                Some(property_value) => self.properties.insert(property_key, property_value),
+                None => self.properties.remove(&property_key),
+            };
+
+            if !changed
+                .iter()
+                .any(|change| change.property_key == property_key)
+            {
+                changed.push(ChangedProperty {
+                    property_key,
+                    old_value,
+                    new_value: None,
+                });
+            }
+        }
+
+        for change in &mut changed {
+            change.new_value = self.properties.get(&change.property_key).cloned();
+        }
+
+        changed.retain(|change| change.old_value != change.new_value);
+
+        if changed
+            .iter()
+            .any(|change| invalidation_of(change.property_key) != Invalidation::None)
+        {
+            self.invalidate();
+        }
+
+        changed
+    }
+
+    /// Returns a listing of all of the properties set on the `Widget`, one per line, identified by
+    /// their registered names where available.  This is intended for debugging.
+    pub fn dump(&self) -> String {
+        let mut keys: Vec<&u32> = self.properties.keys().collect();
+
+        keys.sort();
+        keys.iter()
+            .map(|property_key| {
+                let name =
+                    property_name(**property_key).unwrap_or_else(|| property_key.to_string());
+
+                format!("{} = {:?}\n", name, self.properties[property_key])
+            })
+            .collect()
+    }
+
+    /// Replaces the values supplied by the `Theme`.  Returns `true` if any of the values differ
+    /// from the values previously supplied.
+    pub fn set_theme_properties(&mut self, theme_properties: HashMap<u32, PropertyValue>) -> bool {
+        if self.theme_properties == theme_properties {
+            false
+        } else {
+            self.theme_properties = theme_properties;
+            true
+        }
+    }
+
+    /// Replaces the values inherited from the parent `Widget`.  Returns `true` if any of the values
+    /// differ from the values previously inherited.
+    pub fn set_inherited_properties(
+        &mut self,
+        inherited_properties: HashMap<u32, PropertyValue>,
+    ) -> bool {
+        if self.inherited_properties == inherited_properties {
+            false
+        } else {
+            self.inherited_properties = inherited_properties;
+            true
+        }
     }
 
     /// Sets the invalidated state for the `Widget`.
@@ -92,81 +666,609 @@ impl WidgetProperties {
         self.set_bool(PROPERTY_INVALIDATED);
     }
 
-    /// Stores the color for the specified key.  Format is "r g b a", as numerical values, base 10.
-    /// Sets the invalidate flag afterward.
+    /// Stores the color for the specified key.  Sets the invalidate flag afterward.
     pub fn set_color(&mut self, property_key: u32, color: Color) {
-        self.set(
-            property_key,
-            format!("{} {} {} {}", color.r, color.g, color.b, color.a),
-        );
+        self.set(property_key, PropertyValue::Color(color));
 
         self.invalidate();
     }
 
     /// Sets the size of the `Widget`.
     pub fn set_bounds(&mut self, w: u32, h: u32) {
-        self.set(PROPERTY_SIZE, format!("{} {}", w, h));
+        self.set(PROPERTY_SIZE, PropertyValue::Pair(w, h));
     }
 
-    /// Sets the origin for the `Widget`.  Does not set the invalidate flag, as the repositioning of
-    /// the `Widget` does not require a repaint.
-    pub fn set_origin(&mut self, x: u32, y: u32) {
-        self.set(PROPERTY_ORIGIN, format!("{} {}", x, y));
+    /// Sets the origin for the `Widget`.  Coordinates may be negative, placing the `Widget`
+    /// partially or fully outside of the visible area.  Does not set the invalidate flag, as the
+    /// repositioning of the `Widget` does not require a repaint.
+    pub fn set_origin(&mut self, x: i32, y: i32) {
+        self.set(PROPERTY_ORIGIN, PropertyValue::Point(x, y));
     }
 
     /// Sets a boolean for a given property key.
     pub fn set_bool(&mut self, property_key: u32) {
-        self.set(property_key, String::from("1"));
+        self.set(property_key, PropertyValue::Bool(true));
     }
 
     /// Sets a numeric value to a given property key.
     pub fn set_value(&mut self, property_key: u32, value: i32) {
-        self.set(property_key, format!("{}", value));
+        self.set(property_key, PropertyValue::Integer(value));
     }
 
-    /// Retrieves a color based on the given property key.  If the color cannot be found, the
-    /// `default_color` specified will be returned.
+    /// Sets a text value to a given property key.
+    pub fn set_string(&mut self, property_key: u32, value: String) {
+        self.set(property_key, PropertyValue::String(value));
+    }
+
+    /// Retrieves a color based on the given property key.  Colors stored as text are parsed from
+    /// the "r g b a" (or "r g b") format, as numerical values, base 10, or from any of the formats
+    /// accepted by `colors::parse_color`, such as `#RRGGBB`, `rgba()`, `hsl()` or a CSS color
+    /// name.  Colors stored as a list are read from 3 or 4 numbers in the "r g b a" order.
+    pub fn try_get_color(&self, property_key: u32) -> Result<Color, PropertyError> {
+        match self.lookup(property_key)?.as_ref() {
+            PropertyValue::Color(color) => Ok(*color),
+            PropertyValue::String(text)
+                if !text.trim_start().starts_with(|c: char| c.is_ascii_digit()) =>
+            {
+                parse_color(text).map_err(|error| match error {
+                    ColorError::Invalid => PropertyError::ParseFailure {
+                        key: property_key,
+                        value: text.clone(),
+                    },
+                    ColorError::OutOfRange => PropertyError::OutOfRange {
+                        key: property_key,
+                        value: text.clone(),
+                    },
+                })
+            }
+            value @ PropertyValue::String(_) | value @ PropertyValue::List(_) => {
+                let values = Self::parse_components(property_key, value, &[4, 3], 0, 255)?;
+                let alpha = values.get(3).copied().unwrap_or(255);
+
+                Ok(Color::RGBA(
+                    values[0] as u8,
+                    values[1] as u8,
+                    values[2] as u8,
+                    alpha as u8,
+                ))
+            }
+            _ => Err(PropertyError::WrongType {
+                key: property_key,
+                expected: "a color",
+            }),
+        }
+    }
+
+    /// Retrieves a color based on the given property key.  If the color has not been set, or
+    /// cannot be read, the `default_color` specified will be returned, in place of the default the
+    /// property was registered with.
     pub fn get_color(&self, property_key: u32, default_color: Color) -> Color {
-        if self.properties.contains_key(&property_key) {
-            let tokens: Vec<u8> = self
-                .properties
-                .get(&property_key)
-                .unwrap()
-                .split(' ')
-                .map(|x| (u32::from_str_radix(x, 10).unwrap()) as u8)
-            .collect();
-
-            Color::RGBA(tokens[0], tokens[1], tokens[2], tokens[3])
-        } else {
-            default_color
+        match self.get(property_key) {
+            Some(_) => self.try_get_color(property_key).unwrap_or(default_color),
+            None => default_color,
         }
     }
 
-    /// Retrieves the stored bounds as a tuple.  If the bounds cannot be found, invisible bounds
-    /// are returned (0x0).
+    /// Retrieves the stored bounds as a tuple.  Bounds stored as text are parsed from the "w h"
+    /// format, and bounds stored as a list are read from 2 numbers.
+    pub fn try_get_bounds(&self) -> Result<(u32, u32), PropertyError> {
+        self.try_get_pair(PROPERTY_SIZE)
+    }
+
+    /// Retrieves the stored bounds as a tuple.  If the bounds cannot be found, or cannot be read,
+    /// invisible bounds are returned (0x0).
     pub fn get_bounds(&self) -> (u32, u32) {
-        self.get_tuples(PROPERTY_SIZE, (0_u32, 0_u32))
+        self.try_get_bounds().unwrap_or((0, 0))
+    }
+
+    /// Retrieves the origin of the `Widget`.  Origins stored as text are parsed from the "x y"
+    /// format, and origins stored as a list are read from 2 numbers.
+    pub fn try_get_origin(&self) -> Result<(i32, i32), PropertyError> {
+        self.try_get_point(PROPERTY_ORIGIN)
+    }
+
+    /// Retrieves the origin of the `Widget`.  If the origin cannot be found, or cannot be read, an
+    /// origin of 0x0 is returned.
+    pub fn get_origin(&self) -> (i32, i32) {
+        self.try_get_origin().unwrap_or((0, 0))
     }
 
-    /// Retrieves the origin of the `Widget`.  If the origin cannot be found, an origin of 0x0 is
-    /// returned.
-    pub fn get_origin(&self) -> (u32, u32) {
-        self.get_tuples(PROPERTY_ORIGIN, (0_u32, 0_u32))
+    /// Retrieves the padding of the `Widget`: the space between its border and its content.  If
+    /// the padding cannot be found, or cannot be read, no padding is returned.
+    pub fn get_padding(&self) -> Sides<i32> {
+        self.try_get_sides(PROPERTY_PADDING)
+            .unwrap_or(Sides::uniform(0))
+    }
+
+    /// Retrieves the margin of the `Widget`: the space kept clear around its bounds by layouts.  If
+    /// the margin cannot be found, or cannot be read, no margin is returned.
+    pub fn get_margin(&self) -> Sides<i32> {
+        self.try_get_sides(PROPERTY_MARGIN)
+            .unwrap_or(Sides::uniform(0))
+    }
+
+    /// Retrieves the width of the border on each side of the `Widget`.  `PROPERTY_BORDER_WIDTHS`
+    /// takes precedence over `PROPERTY_BORDER_WIDTH`, and a `PROPERTY_BORDER_STYLE` of `none`
+    /// removes the border.  Negative widths are returned as `0`.
+    pub fn get_border_widths(&self) -> Sides<i32> {
+        if self
+            .get_string(PROPERTY_BORDER_STYLE)
+            .trim()
+            .eq_ignore_ascii_case("none")
+        {
+            return Sides::uniform(0);
+        }
+
+        let widths = self
+            .try_get_sides(PROPERTY_BORDER_WIDTHS)
+            .unwrap_or_else(|_| Sides::uniform(self.get_value(PROPERTY_BORDER_WIDTH)));
+
+        Sides {
+            top: widths.top.max(0),
+            right: widths.right.max(0),
+            bottom: widths.bottom.max(0),
+            left: widths.left.max(0),
+        }
     }
 
-    /// Retrieves the boolean value for a specified property.  If the property has not been set
-    /// with `set_bool`, the return will be `false`.  Otherwise, if the specified key exists, and
-    /// the value is set to `1`, the return value will be `true`.
+    /// Retrieves the bounds of the `Widget` at its origin, which is the area its border is drawn
+    /// around, and the area used for hit testing.  Returns `None` if the `Widget` has no size.
+    pub fn border_rect(&self) -> Option<Rect> {
+        let (x, y) = self.get_origin();
+        let (width, height) = self.get_bounds();
+
+        inset_rect(x, y, width as i32, height as i32, Sides::uniform(0))
+    }
+
+    /// Retrieves the space between the bounds of the `Widget` and its content area on each side,
+    /// which is the width of its border plus its padding.
+    pub fn get_insets(&self) -> Sides<i32> {
+        let border = self.get_border_widths();
+        let padding = self.get_padding();
+
+        Sides {
+            top: border.top + padding.top,
+            right: border.right + padding.right,
+            bottom: border.bottom + padding.bottom,
+            left: border.left + padding.left,
+        }
+    }
+
+    /// Retrieves the area within the border and padding of the `Widget`, in which its content is
+    /// placed.  Returns `None` if the border and padding leave no room for content.
+    pub fn content_rect(&self) -> Option<Rect> {
+        let (x, y) = self.get_origin();
+        let (width, height) = self.get_bounds();
+
+        inset_rect(x, y, width as i32, height as i32, self.get_insets())
+    }
+
+    /// Retrieves the area taken up by the `Widget` including its margin, as used by layouts.
+    /// Returns `None` if the area is empty.
+    pub fn margin_rect(&self) -> Option<Rect> {
+        let (x, y) = self.get_origin();
+        let (width, height) = self.get_bounds();
+        let margin = self.get_margin();
+
+        inset_rect(
+            x,
+            y,
+            width as i32,
+            height as i32,
+            Sides {
+                top: -margin.top,
+                right: -margin.right,
+                bottom: -margin.bottom,
+                left: -margin.left,
+            },
+        )
+    }
+
+    /// Retrieves the cell of a grid that the `Widget` is placed in, as its row, column, row span
+    /// and column span.  Cells stored as text are parsed from the "row column" or
+    /// "row column row_span column_span" format, and cells stored as a list are read from 2 or 4
+    /// numbers.  Spans default to `1`.  A cell that extends beyond `GRID_TRACK_LIMIT` rows or
+    /// columns is out of range.
+    pub fn try_get_grid_cell(&self) -> Result<(u32, u32, u32, u32), PropertyError> {
+        let value = self.lookup(PROPERTY_GRID_CELL)?;
+        let limit = i64::from(GRID_TRACK_LIMIT);
+        let values = Self::parse_components(PROPERTY_GRID_CELL, &value, &[2, 4], 0, limit)?;
+        let span = |index: usize| values.get(index).map_or(1, |span| (*span).max(1));
+        let (row, column, row_span, column_span) = (values[0], values[1], span(2), span(3));
+
+        if row + row_span > limit || column + column_span > limit {
+            return Err(PropertyError::OutOfRange {
+                key: PROPERTY_GRID_CELL,
+                value: format!("{} {} {} {}", row, column, row_span, column_span),
+            });
+        }
+
+        Ok((
+            row as u32,
+            column as u32,
+            row_span as u32,
+            column_span as u32,
+        ))
+    }
+
+    /// Retrieves the boolean value for a specified property.  Booleans stored as text are read
+    /// from `1`, `0`, `true` or `false`.
+    pub fn try_get_bool(&self, property_key: u32) -> Result<bool, PropertyError> {
+        match self.lookup(property_key)?.as_ref() {
+            PropertyValue::Bool(value) => Ok(*value),
+            PropertyValue::String(text) => match text.trim() {
+                "1" | "true" => Ok(true),
+                "0" | "false" => Ok(false),
+                _ => Err(PropertyError::ParseFailure {
+                    key: property_key,
+                    value: text.clone(),
+                }),
+            },
+            _ => Err(PropertyError::WrongType {
+                key: property_key,
+                expected: "a boolean",
+            }),
+        }
+    }
+
+    /// Retrieves the boolean value for a specified property.  If the property has not been set,
+    /// and has no registered default, or the value cannot be read as a boolean, the return will be
+    /// `false`.
     pub fn get_bool(&self, property_key: u32) -> bool {
-        self.properties.contains_key(&property_key) && self.properties.get(&property_key).unwrap() == "1"
+        self.try_get_bool(property_key).unwrap_or(false)
     }
 
-    /// Retrieves a numeric value assigned to a property as an `i32` value.
+    /// Retrieves a numeric value assigned to a property as an `i32` value.  Numbers stored as text
+    /// are parsed as base 10.
+    pub fn try_get_value(&self, property_key: u32) -> Result<i32, PropertyError> {
+        match self.lookup(property_key)?.as_ref() {
+            PropertyValue::Integer(value) => Ok(*value),
+            value @ PropertyValue::String(_) => {
+                let values = Self::parse_components(
+                    property_key,
+                    value,
+                    &[1],
+                    i64::from(i32::MIN),
+                    i64::from(i32::MAX),
+                )?;
+
+                Ok(values[0] as i32)
+            }
+            _ => Err(PropertyError::WrongType {
+                key: property_key,
+                expected: "a number",
+            }),
+        }
+    }
+
+    /// Retrieves a numeric value assigned to a property as an `i32` value.  If the value has not
+    /// been set, and has no registered default, or cannot be read as a number, `0` is returned.
     pub fn get_value(&self, property_key: u32) -> i32 {
-        i32::from_str_radix(self.properties
-            .get(&property_key)
-            .unwrap_or(&String::from("0")), 10)
-            .unwrap()
+        self.try_get_value(property_key).unwrap_or(0)
+    }
+
+    /// Retrieves a value for each side of the `Widget`, such as border widths.  Values stored as
+    /// text are parsed from the "top right bottom left" format, and values stored as a list are
+    /// read from numbers in the same order.  As with CSS, one value applies to every side, and two
+    /// values apply to the top and bottom, then the left and right sides.
+    pub fn try_get_sides(&self, property_key: u32) -> Result<Sides<i32>, PropertyError> {
+        match self.lookup(property_key)?.as_ref() {
+            PropertyValue::Integer(value) => Ok(Sides::uniform(*value)),
+            value @ PropertyValue::String(_) | value @ PropertyValue::List(_) => {
+                let values: Vec<i32> = Self::parse_components(
+                    property_key,
+                    value,
+                    &[4, 2, 1],
+                    i64::from(i32::MIN),
+                    i64::from(i32::MAX),
+                )?
+                .into_iter()
+                .map(|value| value as i32)
+                .collect();
+
+                Ok(Sides::from_shorthand(&values).unwrap())
+            }
+            _ => Err(PropertyError::WrongType {
+                key: property_key,
+                expected: "a value for each side",
+            }),
+        }
+    }
+
+    /// Retrieves a color for each side of the `Widget`, such as border colors.  Colors stored as a
+    /// list may contain colors, or any text accepted by `try_get_color`.  Colors stored as text are
+    /// separated by spaces, so they can only use the hexadecimal or named formats (ie.
+    /// `"red #00ff00 red #00ff00"`).  Colors apply to sides in the same order as `try_get_sides`.
+    pub fn try_get_side_colors(&self, property_key: u32) -> Result<Sides<Color>, PropertyError> {
+        let parse = |text: &str| {
+            parse_color(text).map_err(|_| PropertyError::ParseFailure {
+                key: property_key,
+                value: String::from(text),
+            })
+        };
+        let colors: Vec<Color> = match self.lookup(property_key)?.as_ref() {
+            PropertyValue::Color(color) => vec![*color],
+            PropertyValue::String(text) => text
+                .split_whitespace()
+                .map(parse)
+                .collect::<Result<_, _>>()?,
+            PropertyValue::List(items) => items
+                .iter()
+                .map(|item| match item {
+                    PropertyValue::Color(color) => Ok(*color),
+                    PropertyValue::String(text) => parse(text),
+                    _ => Err(PropertyError::WrongType {
+                        key: property_key,
+                        expected: "a list of colors",
+                    }),
+                })
+                .collect::<Result<_, _>>()?,
+            _ => {
+                return Err(PropertyError::WrongType {
+                    key: property_key,
+                    expected: "a color for each side",
+                })
+            }
+        };
+
+        Sides::from_shorthand(&colors).ok_or(PropertyError::WrongArity {
+            key: property_key,
+            expected: 4,
+            found: colors.len(),
+        })
     }
 
+    /// Retrieves a text value assigned to a property.
+    pub fn try_get_string(&self, property_key: u32) -> Result<String, PropertyError> {
+        match self.lookup(property_key)?.as_ref() {
+            PropertyValue::String(value) => Ok(value.clone()),
+            _ => Err(PropertyError::WrongType {
+                key: property_key,
+                expected: "text",
+            }),
+        }
+    }
+
+    /// Retrieves a text value assigned to a property.  If the value has not been set, and has no
+    /// registered default, or is not a text value, an empty `String` is returned.
+    pub fn get_string(&self, property_key: u32) -> String {
+        self.try_get_string(property_key).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::property_registry::PROPERTY_CUSTOM_START;
+
+    #[test]
+    fn getters_fall_back_to_registered_defaults() {
+        let properties = WidgetProperties::default();
+
+        assert_eq!(properties.get_value(PROPERTY_DRAG_THRESHOLD), 4);
+        assert_eq!(properties.get_value(PROPERTY_FOCUS_RING_WIDTH), 2);
+        assert_eq!(properties.get_string(PROPERTY_BORDER_STYLE), "solid");
+        assert_eq!(
+            properties.try_get_color(PROPERTY_MAIN_COLOR),
+            Ok(Color::RGB(255, 255, 255))
+        );
+    }
+
+    #[test]
+    fn get_color_prefers_the_given_default_to_the_registered_default() {
+        let mut properties = WidgetProperties::default();
+
+        assert_eq!(
+            properties.get_color(PROPERTY_MAIN_COLOR, Color::RGB(1, 2, 3)),
+            Color::RGB(1, 2, 3)
+        );
+
+        properties.set_color(PROPERTY_MAIN_COLOR, Color::RGB(4, 5, 6));
+
+        assert_eq!(
+            properties.get_color(PROPERTY_MAIN_COLOR, Color::RGB(1, 2, 3)),
+            Color::RGB(4, 5, 6)
+        );
+    }
+
+    #[test]
+    fn set_values_override_registered_defaults() {
+        let mut properties = WidgetProperties::default();
+
+        properties.set_value(PROPERTY_DRAG_THRESHOLD, 10);
+
+        assert_eq!(properties.get_value(PROPERTY_DRAG_THRESHOLD), 10);
+    }
+
+    #[test]
+    fn getters_report_missing_keys_without_defaults() {
+        let properties = WidgetProperties::default();
+
+        assert_eq!(
+            properties.try_get_value(PROPERTY_FONT_SIZE),
+            Err(PropertyError::MissingKey(PROPERTY_FONT_SIZE))
+        );
+        assert_eq!(properties.get_or_default(PROPERTY_FONT_SIZE), None);
+    }
+
+    #[test]
+    fn grid_cell_spans_default_to_one() {
+        let mut properties = WidgetProperties::default();
+
+        properties.set_string(PROPERTY_GRID_CELL, String::from("2 3"));
+        assert_eq!(properties.try_get_grid_cell(), Ok((2, 3, 1, 1)));
+
+        properties.set_string(PROPERTY_GRID_CELL, String::from("2 3 0 4"));
+        assert_eq!(properties.try_get_grid_cell(), Ok((2, 3, 1, 4)));
+    }
+
+    #[test]
+    fn grid_cell_rejects_cells_beyond_the_track_limit() {
+        let mut properties = WidgetProperties::default();
+        let limit = GRID_TRACK_LIMIT;
+
+        properties.set_string(PROPERTY_GRID_CELL, format!("0 {} 1 1", limit - 1));
+        assert_eq!(properties.try_get_grid_cell(), Ok((0, limit - 1, 1, 1)));
+
+        for cell in &[
+            format!("0 {} 1 2", limit - 1),
+            format!("{} 0 2 1", limit - 1),
+            format!("0 0 1 {}", u32::MAX),
+            String::from("-1 0"),
+        ] {
+            properties.set_string(PROPERTY_GRID_CELL, cell.clone());
+            assert!(
+                matches!(
+                    properties.try_get_grid_cell(),
+                    Err(PropertyError::OutOfRange { .. })
+                ),
+                "{}",
+                cell
+            );
+        }
+    }
+
+    #[test]
+    fn getters_report_the_wrong_number_of_components() {
+        let mut properties = WidgetProperties::default();
+
+        properties.set_string(PROPERTY_SIZE, String::from("10"));
+        assert_eq!(
+            properties.try_get_bounds(),
+            Err(PropertyError::WrongArity {
+                key: PROPERTY_SIZE,
+                expected: 2,
+                found: 1,
+            })
+        );
+
+        properties.set(
+            PROPERTY_ORIGIN,
+            PropertyValue::List(vec![
+                PropertyValue::Integer(1),
+                PropertyValue::Integer(2),
+                PropertyValue::Integer(3),
+            ]),
+        );
+        assert_eq!(
+            properties.try_get_origin(),
+            Err(PropertyError::WrongArity {
+                key: PROPERTY_ORIGIN,
+                expected: 2,
+                found: 3,
+            })
+        );
+
+        properties.set_string(PROPERTY_MAIN_COLOR, String::from("1 2"));
+        assert_eq!(
+            properties.try_get_color(PROPERTY_MAIN_COLOR),
+            Err(PropertyError::WrongArity {
+                key: PROPERTY_MAIN_COLOR,
+                expected: 4,
+                found: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn getters_report_components_out_of_range() {
+        let mut properties = WidgetProperties::default();
+
+        properties.set_string(PROPERTY_MAIN_COLOR, String::from("0 0 300"));
+        assert_eq!(
+            properties.try_get_color(PROPERTY_MAIN_COLOR),
+            Err(PropertyError::OutOfRange {
+                key: PROPERTY_MAIN_COLOR,
+                value: String::from("300"),
+            })
+        );
+
+        properties.set_string(PROPERTY_SIZE, String::from("-1 5"));
+        assert_eq!(
+            properties.try_get_bounds(),
+            Err(PropertyError::OutOfRange {
+                key: PROPERTY_SIZE,
+                value: String::from("-1"),
+            })
+        );
+
+        properties.set_string(PROPERTY_BORDER_WIDTH, String::from("99999999999"));
+        assert_eq!(
+            properties.try_get_value(PROPERTY_BORDER_WIDTH),
+            Err(PropertyError::OutOfRange {
+                key: PROPERTY_BORDER_WIDTH,
+                value: String::from("99999999999"),
+            })
+        );
+    }
+
+    #[test]
+    fn getters_report_components_that_are_not_numbers() {
+        let mut properties = WidgetProperties::default();
+
+        properties.set_string(PROPERTY_SIZE, String::from("10 wide"));
+        assert_eq!(
+            properties.try_get_bounds(),
+            Err(PropertyError::ParseFailure {
+                key: PROPERTY_SIZE,
+                value: String::from("wide"),
+            })
+        );
+
+        properties.set_string(PROPERTY_BORDER_WIDTH, String::from("ten"));
+        assert_eq!(
+            properties.try_get_value(PROPERTY_BORDER_WIDTH),
+            Err(PropertyError::ParseFailure {
+                key: PROPERTY_BORDER_WIDTH,
+                value: String::from("ten"),
+            })
+        );
+
+        properties.set_string(PROPERTY_HIDDEN, String::from("maybe"));
+        assert_eq!(
+            properties.try_get_bool(PROPERTY_HIDDEN),
+            Err(PropertyError::ParseFailure {
+                key: PROPERTY_HIDDEN,
+                value: String::from("maybe"),
+            })
+        );
+    }
+
+    #[test]
+    fn getters_report_missing_keys_and_wrong_types() {
+        let mut properties = WidgetProperties::default();
+
+        assert_eq!(
+            properties.try_get_string(PROPERTY_TEXT),
+            Err(PropertyError::MissingKey(PROPERTY_TEXT))
+        );
+        assert_eq!(
+            properties.try_get_pair(PROPERTY_CUSTOM_START - 1),
+            Err(PropertyError::MissingKey(PROPERTY_CUSTOM_START - 1))
+        );
+
+        properties.set_bool(PROPERTY_SIZE);
+        assert_eq!(
+            properties.try_get_bounds(),
+            Err(PropertyError::WrongType {
+                key: PROPERTY_SIZE,
+                expected: "a pair",
+            })
+        );
+
+        properties.set(
+            PROPERTY_SIZE,
+            PropertyValue::List(vec![
+                PropertyValue::Integer(1),
+                PropertyValue::String(String::from("2")),
+            ]),
+        );
+        assert_eq!(
+            properties.try_get_bounds(),
+            Err(PropertyError::WrongType {
+                key: PROPERTY_SIZE,
+                expected: "a list of numbers",
+            })
+        );
+    }
 }
@@ -0,0 +1,710 @@
+// Pushrod Widgets
+// Property Registry
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::properties::{
+    PropertyValue, PROPERTY_ALIGNMENT, PROPERTY_ANCHORS, PROPERTY_BACKGROUND_GRADIENT,
+    PROPERTY_BACKGROUND_IMAGE, PROPERTY_BACKGROUND_IMAGE_MODE, PROPERTY_BORDER_COLOR,
+    PROPERTY_BORDER_COLORS, PROPERTY_BORDER_RADIUS, PROPERTY_BORDER_STYLE, PROPERTY_BORDER_WIDTH,
+    PROPERTY_BORDER_WIDTHS, PROPERTY_DISABLED, PROPERTY_DRAG_THRESHOLD, PROPERTY_FOCUSABLE,
+    PROPERTY_FOCUSED, PROPERTY_FOCUS_RING_COLOR, PROPERTY_FOCUS_RING_WIDTH, PROPERTY_FONT_NAME,
+    PROPERTY_FONT_SIZE, PROPERTY_GRID_CELL, PROPERTY_GRID_COLUMNS, PROPERTY_HIDDEN,
+    PROPERTY_HOVERED, PROPERTY_HOVER_COLOR, PROPERTY_INVALIDATED, PROPERTY_MAIN_COLOR,
+    PROPERTY_MARGIN, PROPERTY_MAX_SIZE, PROPERTY_MIN_SIZE, PROPERTY_NATIVE_WIDGET_ADDER,
+    PROPERTY_OPACITY, PROPERTY_ORIGIN, PROPERTY_PADDING, PROPERTY_PREFERRED_SIZE,
+    PROPERTY_RELATIVE_ORIGIN, PROPERTY_RELATIVE_SIZE, PROPERTY_SHADOW_BLUR, PROPERTY_SHADOW_COLOR,
+    PROPERTY_SHADOW_OFFSET, PROPERTY_SHADOW_SPREAD, PROPERTY_SIZE, PROPERTY_SPACING,
+    PROPERTY_STRETCH, PROPERTY_TAB_INDEX, PROPERTY_TEXT, PROPERTY_TEXT_COLOR,
+};
+use sdl2::pixels::Color;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+/// This is the first key handed out by `register_property`.  Keys below this value are reserved
+/// for the properties defined by this library.
+pub const PROPERTY_CUSTOM_START: u32 = 65536;
+
+/// This is the type of value a property is expected to store.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PropertyKind {
+    Bool,
+    Integer,
+    Pair,
+    Point,
+    Color,
+    String,
+    Rect,
+    List,
+
+    /// The property stores a value for each side of a `Widget`, as a single number, or a list of
+    /// 2 or 4 numbers.
+    Sides,
+
+    /// The property may store any type of value.
+    Any,
+}
+
+impl PropertyKind {
+    /// Determines whether or not a value can be stored for a property of this kind.  Text is
+    /// always accepted, as it is parsed when the property is read, and lists are accepted for
+    /// pairs, points, colors and sides.
+    pub fn accepts(&self, value: &PropertyValue) -> bool {
+        matches!(
+            (self, value),
+            (PropertyKind::Any, _)
+                | (_, PropertyValue::String(_))
+                | (PropertyKind::Bool, PropertyValue::Bool(_))
+                | (PropertyKind::Integer, PropertyValue::Integer(_))
+                | (PropertyKind::Pair, PropertyValue::Pair(_, _))
+                | (PropertyKind::Pair, PropertyValue::List(_))
+                | (PropertyKind::Point, PropertyValue::Point(_, _))
+                | (PropertyKind::Point, PropertyValue::Pair(_, _))
+                | (PropertyKind::Point, PropertyValue::List(_))
+                | (PropertyKind::Color, PropertyValue::Color(_))
+                | (PropertyKind::Color, PropertyValue::List(_))
+                | (PropertyKind::Rect, PropertyValue::Rect(_))
+                | (PropertyKind::List, PropertyValue::List(_))
+                | (PropertyKind::Sides, PropertyValue::Integer(_))
+                | (PropertyKind::Sides, PropertyValue::List(_))
+        )
+    }
+}
+
+/// This describes what needs to happen to a `Widget` when a property changes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Invalidation {
+    /// The `Widget` needs to be redrawn.
+    Repaint,
+
+    /// The `Widget` and its surrounding layout need to be recomputed and redrawn.
+    Relayout,
+
+    /// The change does not affect the drawing of the `Widget`.
+    None,
+}
+
+/// This is an error returned when a property cannot be registered.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RegistrationError {
+    /// A property has already been registered with the given name, with a different description.
+    AlreadyRegistered(String),
+}
+
+impl fmt::Display for RegistrationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RegistrationError::AlreadyRegistered(name) => {
+                write!(f, "property '{}' has already been registered", name)
+            }
+        }
+    }
+}
+
+impl Error for RegistrationError {}
+
+/// This is the description of a registered property.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertyDescriptor {
+    pub key: u32,
+    pub name: String,
+    pub kind: PropertyKind,
+    pub default: Option<PropertyValue>,
+    pub invalidation: Invalidation,
+}
+
+#[derive(Default, Clone)]
+struct PropertyRegistry {
+    descriptors: HashMap<u32, PropertyDescriptor>,
+    names: HashMap<String, u32>,
+    next_key: u32,
+}
+
+impl PropertyRegistry {
+    fn insert(&mut self, descriptor: PropertyDescriptor) {
+        self.names.insert(descriptor.name.clone(), descriptor.key);
+        self.descriptors.insert(descriptor.key, descriptor);
+    }
+
+    fn builtin(
+        &mut self,
+        key: u32,
+        name: &str,
+        kind: PropertyKind,
+        default: Option<PropertyValue>,
+        invalidation: Invalidation,
+    ) {
+        self.insert(PropertyDescriptor {
+            key,
+            name: String::from(name),
+            kind,
+            default,
+            invalidation,
+        });
+    }
+
+    fn with_builtins() -> Self {
+        let mut registry = PropertyRegistry {
+            next_key: PROPERTY_CUSTOM_START,
+            ..PropertyRegistry::default()
+        };
+
+        registry.builtin(
+            PROPERTY_NATIVE_WIDGET_ADDER,
+            "native_widget_adder",
+            PropertyKind::Any,
+            None,
+            Invalidation::None,
+        );
+        registry.builtin(
+            PROPERTY_INVALIDATED,
+            "invalidated",
+            PropertyKind::Bool,
+            None,
+            Invalidation::None,
+        );
+        registry.builtin(
+            PROPERTY_HIDDEN,
+            "hidden",
+            PropertyKind::Bool,
+            Some(PropertyValue::Bool(false)),
+            Invalidation::Repaint,
+        );
+        registry.builtin(
+            PROPERTY_ORIGIN,
+            "origin",
+            PropertyKind::Point,
+            Some(PropertyValue::Point(0, 0)),
+            Invalidation::None,
+        );
+        registry.builtin(
+            PROPERTY_SIZE,
+            "size",
+            PropertyKind::Pair,
+            Some(PropertyValue::Pair(0, 0)),
+            Invalidation::Relayout,
+        );
+        registry.builtin(
+            PROPERTY_TEXT,
+            "text",
+            PropertyKind::String,
+            None,
+            Invalidation::Repaint,
+        );
+        registry.builtin(
+            PROPERTY_MAIN_COLOR,
+            "main_color",
+            PropertyKind::Color,
+            Some(PropertyValue::Color(Color::RGB(255, 255, 255))),
+            Invalidation::Repaint,
+        );
+        registry.builtin(
+            PROPERTY_BORDER_COLOR,
+            "border_color",
+            PropertyKind::Color,
+            Some(PropertyValue::Color(Color::RGB(0, 0, 0))),
+            Invalidation::Repaint,
+        );
+        registry.builtin(
+            PROPERTY_BORDER_WIDTH,
+            "border_width",
+            PropertyKind::Integer,
+            Some(PropertyValue::Integer(0)),
+            Invalidation::Repaint,
+        );
+        registry.builtin(
+            PROPERTY_FONT_NAME,
+            "font_name",
+            PropertyKind::String,
+            None,
+            Invalidation::Repaint,
+        );
+        registry.builtin(
+            PROPERTY_FONT_SIZE,
+            "font_size",
+            PropertyKind::Integer,
+            None,
+            Invalidation::Repaint,
+        );
+        registry.builtin(
+            PROPERTY_PADDING,
+            "padding",
+            PropertyKind::Sides,
+            Some(PropertyValue::Integer(0)),
+            Invalidation::Relayout,
+        );
+        registry.builtin(
+            PROPERTY_HOVERED,
+            "hovered",
+            PropertyKind::Bool,
+            Some(PropertyValue::Bool(false)),
+            Invalidation::Repaint,
+        );
+        registry.builtin(
+            PROPERTY_DISABLED,
+            "disabled",
+            PropertyKind::Bool,
+            Some(PropertyValue::Bool(false)),
+            Invalidation::Repaint,
+        );
+        registry.builtin(
+            PROPERTY_TEXT_COLOR,
+            "text_color",
+            PropertyKind::Color,
+            Some(PropertyValue::Color(Color::RGB(0, 0, 0))),
+            Invalidation::Repaint,
+        );
+        registry.builtin(
+            PROPERTY_OPACITY,
+            "opacity",
+            PropertyKind::Integer,
+            Some(PropertyValue::Integer(255)),
+            Invalidation::Repaint,
+        );
+        registry.builtin(
+            PROPERTY_BORDER_WIDTHS,
+            "border_widths",
+            PropertyKind::Sides,
+            None,
+            Invalidation::Repaint,
+        );
+        registry.builtin(
+            PROPERTY_BORDER_COLORS,
+            "border_colors",
+            PropertyKind::List,
+            None,
+            Invalidation::Repaint,
+        );
+        registry.builtin(
+            PROPERTY_BORDER_RADIUS,
+            "border_radius",
+            PropertyKind::Integer,
+            Some(PropertyValue::Integer(0)),
+            Invalidation::Repaint,
+        );
+        registry.builtin(
+            PROPERTY_BORDER_STYLE,
+            "border_style",
+            PropertyKind::String,
+            Some(PropertyValue::String(String::from("solid"))),
+            Invalidation::Repaint,
+        );
+        registry.builtin(
+            PROPERTY_BACKGROUND_GRADIENT,
+            "background_gradient",
+            PropertyKind::String,
+            None,
+            Invalidation::Repaint,
+        );
+        registry.builtin(
+            PROPERTY_BACKGROUND_IMAGE,
+            "background_image",
+            PropertyKind::String,
+            None,
+            Invalidation::Repaint,
+        );
+        registry.builtin(
+            PROPERTY_BACKGROUND_IMAGE_MODE,
+            "background_image_mode",
+            PropertyKind::String,
+            Some(PropertyValue::String(String::from("stretch"))),
+            Invalidation::Repaint,
+        );
+        registry.builtin(
+            PROPERTY_SHADOW_OFFSET,
+            "shadow_offset",
+            PropertyKind::Point,
+            Some(PropertyValue::Point(0, 0)),
+            Invalidation::Repaint,
+        );
+        registry.builtin(
+            PROPERTY_SHADOW_BLUR,
+            "shadow_blur",
+            PropertyKind::Integer,
+            Some(PropertyValue::Integer(0)),
+            Invalidation::Repaint,
+        );
+        registry.builtin(
+            PROPERTY_SHADOW_SPREAD,
+            "shadow_spread",
+            PropertyKind::Integer,
+            Some(PropertyValue::Integer(0)),
+            Invalidation::Repaint,
+        );
+        registry.builtin(
+            PROPERTY_SHADOW_COLOR,
+            "shadow_color",
+            PropertyKind::Color,
+            None,
+            Invalidation::Repaint,
+        );
+        registry.builtin(
+            PROPERTY_MARGIN,
+            "margin",
+            PropertyKind::Sides,
+            Some(PropertyValue::Integer(0)),
+            Invalidation::Relayout,
+        );
+        registry.builtin(
+            PROPERTY_RELATIVE_SIZE,
+            "relative_size",
+            PropertyKind::List,
+            None,
+            Invalidation::Relayout,
+        );
+        registry.builtin(
+            PROPERTY_RELATIVE_ORIGIN,
+            "relative_origin",
+            PropertyKind::List,
+            None,
+            Invalidation::Relayout,
+        );
+        registry.builtin(
+            PROPERTY_ANCHORS,
+            "anchors",
+            PropertyKind::Sides,
+            None,
+            Invalidation::Relayout,
+        );
+        registry.builtin(
+            PROPERTY_SPACING,
+            "spacing",
+            PropertyKind::Integer,
+            Some(PropertyValue::Integer(0)),
+            Invalidation::Relayout,
+        );
+        registry.builtin(
+            PROPERTY_ALIGNMENT,
+            "alignment",
+            PropertyKind::String,
+            Some(PropertyValue::String(String::from("stretch"))),
+            Invalidation::Relayout,
+        );
+        registry.builtin(
+            PROPERTY_GRID_COLUMNS,
+            "grid_columns",
+            PropertyKind::Integer,
+            Some(PropertyValue::Integer(1)),
+            Invalidation::Relayout,
+        );
+        registry.builtin(
+            PROPERTY_STRETCH,
+            "stretch",
+            PropertyKind::Integer,
+            Some(PropertyValue::Integer(0)),
+            Invalidation::Relayout,
+        );
+        registry.builtin(
+            PROPERTY_MIN_SIZE,
+            "min_size",
+            PropertyKind::Pair,
+            None,
+            Invalidation::Relayout,
+        );
+        registry.builtin(
+            PROPERTY_MAX_SIZE,
+            "max_size",
+            PropertyKind::Pair,
+            None,
+            Invalidation::Relayout,
+        );
+        registry.builtin(
+            PROPERTY_GRID_CELL,
+            "grid_cell",
+            PropertyKind::List,
+            None,
+            Invalidation::Relayout,
+        );
+        registry.builtin(
+            PROPERTY_PREFERRED_SIZE,
+            "preferred_size",
+            PropertyKind::Pair,
+            None,
+            Invalidation::Relayout,
+        );
+        registry.builtin(
+            PROPERTY_HOVER_COLOR,
+            "hover_color",
+            PropertyKind::Color,
+            None,
+            Invalidation::Repaint,
+        );
+        registry.builtin(
+            PROPERTY_DRAG_THRESHOLD,
+            "drag_threshold",
+            PropertyKind::Integer,
+            Some(PropertyValue::Integer(4)),
+            Invalidation::None,
+        );
+        registry.builtin(
+            PROPERTY_FOCUSABLE,
+            "focusable",
+            PropertyKind::Bool,
+            Some(PropertyValue::Bool(false)),
+            Invalidation::None,
+        );
+        registry.builtin(
+            PROPERTY_TAB_INDEX,
+            "tab_index",
+            PropertyKind::Integer,
+            Some(PropertyValue::Integer(0)),
+            Invalidation::None,
+        );
+        registry.builtin(
+            PROPERTY_FOCUSED,
+            "focused",
+            PropertyKind::Bool,
+            Some(PropertyValue::Bool(false)),
+            Invalidation::Repaint,
+        );
+        registry.builtin(
+            PROPERTY_FOCUS_RING_COLOR,
+            "focus_ring_color",
+            PropertyKind::Color,
+            None,
+            Invalidation::Repaint,
+        );
+        registry.builtin(
+            PROPERTY_FOCUS_RING_WIDTH,
+            "focus_ring_width",
+            PropertyKind::Integer,
+            Some(PropertyValue::Integer(2)),
+            Invalidation::Repaint,
+        );
+
+        registry
+    }
+}
+
+/// Returns the properties defined by this library.  They never change, so they are kept in a
+/// table of their own, which can be read without locking the registry.
+fn builtins() -> &'static PropertyRegistry {
+    static BUILTINS: OnceLock<PropertyRegistry> = OnceLock::new();
+
+    BUILTINS.get_or_init(PropertyRegistry::with_builtins)
+}
+
+fn registry() -> MutexGuard<'static, PropertyRegistry> {
+    static REGISTRY: OnceLock<Mutex<PropertyRegistry>> = OnceLock::new();
+
+    REGISTRY
+        .get_or_init(|| Mutex::new(builtins().clone()))
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Registers a new property with a unique name, returning the unique key that identifies it.
+/// Registering the same name again with an identical description returns the previously issued
+/// key, so that registration can safely be repeated.  Registering the same name with a different
+/// description returns a `RegistrationError::AlreadyRegistered` error.
+pub fn register_property(
+    name: &str,
+    kind: PropertyKind,
+    default: Option<PropertyValue>,
+    invalidation: Invalidation,
+) -> Result<u32, RegistrationError> {
+    let mut registry = registry();
+
+    if let Some(existing_key) = registry.names.get(name).copied() {
+        let existing = &registry.descriptors[&existing_key];
+
+        return if existing.kind == kind
+            && existing.default == default
+            && existing.invalidation == invalidation
+        {
+            Ok(existing_key)
+        } else {
+            Err(RegistrationError::AlreadyRegistered(String::from(name)))
+        };
+    }
+
+    let key = registry.next_key;
+
+    registry.next_key += 1;
+    registry.insert(PropertyDescriptor {
+        key,
+        name: String::from(name),
+        kind,
+        default,
+        invalidation,
+    });
+
+    Ok(key)
+}
+
+/// Retrieves the key of a property by its name (ie. `"main_color"`), or `None` if no property has
+/// been registered with the name.
+pub fn property_key(name: &str) -> Option<u32> {
+    registry().names.get(name).copied()
+}
+
+/// Retrieves the name of a property by its key, or `None` if the key has not been registered.
+pub fn property_name(property_key: u32) -> Option<String> {
+    registry()
+        .descriptors
+        .get(&property_key)
+        .map(|descriptor| descriptor.name.clone())
+}
+
+/// Retrieves the full description of a property by its key, or `None` if the key has not been
+/// registered.
+pub fn property_descriptor(property_key: u32) -> Option<PropertyDescriptor> {
+    if property_key < PROPERTY_CUSTOM_START {
+        return builtins().descriptors.get(&property_key).cloned();
+    }
+
+    registry().descriptors.get(&property_key).cloned()
+}
+
+/// Retrieves the default value of a property by its key, or `None` if the key has not been
+/// registered, or was registered without a default.
+pub fn property_default(property_key: u32) -> Option<PropertyValue> {
+    default_of(property_key).map(Cow::into_owned)
+}
+
+/// Retrieves the default value of a property as `property_default` does.  The defaults of the
+/// properties defined by this library are borrowed from their table, rather than copied.
+pub(crate) fn default_of(property_key: u32) -> Option<Cow<'static, PropertyValue>> {
+    if property_key < PROPERTY_CUSTOM_START {
+        return builtins()
+            .descriptors
+            .get(&property_key)
+            .and_then(|descriptor| descriptor.default.as_ref())
+            .map(Cow::Borrowed);
+    }
+
+    registry()
+        .descriptors
+        .get(&property_key)
+        .and_then(|descriptor| descriptor.default.clone())
+        .map(Cow::Owned)
+}
+
+/// Retrieves the descriptions of all registered properties, ordered by key.
+pub fn property_descriptors() -> Vec<PropertyDescriptor> {
+    let mut descriptors: Vec<PropertyDescriptor> =
+        registry().descriptors.values().cloned().collect();
+
+    descriptors.sort_by_key(|descriptor| descriptor.key);
+    descriptors
+}
+
+/// Retrieves the `Invalidation` behavior of a property.  Properties that have not been registered
+/// are treated as requiring a `Repaint`.
+pub fn invalidation_of(property_key: u32) -> Invalidation {
+    let invalidation = |registry: &PropertyRegistry| {
+        registry
+            .descriptors
+            .get(&property_key)
+            .map(|descriptor| descriptor.invalidation)
+            .unwrap_or(Invalidation::Repaint)
+    };
+
+    if property_key < PROPERTY_CUSTOM_START {
+        invalidation(builtins())
+    } else {
+        invalidation(&registry())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn register_property_repeats_identical_registrations() {
+        let key = register_property(
+            "test_repeat",
+            PropertyKind::Integer,
+            None,
+            Invalidation::None,
+        );
+
+        assert!(key.is_ok());
+        assert_eq!(
+            register_property(
+                "test_repeat",
+                PropertyKind::Integer,
+                None,
+                Invalidation::None
+            ),
+            key
+        );
+    }
+
+    #[test]
+    fn register_property_rejects_conflicting_registrations() {
+        register_property(
+            "test_conflict",
+            PropertyKind::Integer,
+            None,
+            Invalidation::None,
+        )
+        .unwrap();
+
+        assert_eq!(
+            register_property(
+                "test_conflict",
+                PropertyKind::Bool,
+                None,
+                Invalidation::None
+            ),
+            Err(RegistrationError::AlreadyRegistered(String::from(
+                "test_conflict"
+            )))
+        );
+    }
+
+    #[test]
+    fn property_default_returns_registered_default() {
+        assert_eq!(
+            property_default(PROPERTY_DRAG_THRESHOLD),
+            Some(PropertyValue::Integer(4))
+        );
+        assert_eq!(property_default(PROPERTY_FONT_SIZE), None);
+    }
+
+    #[test]
+    fn custom_properties_are_described_by_the_registry() {
+        let key = register_property(
+            "test_custom_default",
+            PropertyKind::Integer,
+            Some(PropertyValue::Integer(7)),
+            Invalidation::Relayout,
+        )
+        .unwrap();
+
+        assert!(key >= PROPERTY_CUSTOM_START);
+        assert_eq!(property_default(key), Some(PropertyValue::Integer(7)));
+        assert_eq!(invalidation_of(key), Invalidation::Relayout);
+        assert_eq!(
+            property_descriptor(key).map(|descriptor| descriptor.name),
+            Some(String::from("test_custom_default"))
+        );
+    }
+
+    #[test]
+    fn shadow_properties_repaint() {
+        for property_key in &[
+            PROPERTY_SHADOW_OFFSET,
+            PROPERTY_SHADOW_BLUR,
+            PROPERTY_SHADOW_SPREAD,
+            PROPERTY_SHADOW_COLOR,
+        ] {
+            assert_eq!(invalidation_of(*property_key), Invalidation::Repaint);
+        }
+    }
+}
@@ -0,0 +1,421 @@
+// Pushrod Widgets
+// Scene Loader
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::caches::WidgetCache;
+use crate::properties::PropertyValue;
+use crate::property_registry::{property_descriptor, property_key};
+use crate::widget_registry::WidgetRegistry;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// This is the text format of a scene file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SceneFormat {
+    Json,
+    Ron,
+    Toml,
+}
+
+impl SceneFormat {
+    /// Determines the format of a scene file by its extension (`.json`, `.ron` or `.toml`).
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "json" => Some(SceneFormat::Json),
+            "ron" => Some(SceneFormat::Ron),
+            "toml" => Some(SceneFormat::Toml),
+            _ => None,
+        }
+    }
+}
+
+/// This is an error that is returned when a scene cannot be loaded.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SceneError {
+    /// The scene file could not be read, or its format could not be determined.
+    Io(String),
+
+    /// The scene text could not be parsed in its `SceneFormat`.
+    Format(String),
+
+    /// A `Widget` uses a type that has not been registered in the `WidgetRegistry`.
+    UnknownType { widget: String, widget_type: String },
+
+    /// A `Widget` uses a name that is already in use by another `Widget`.
+    DuplicateName(String),
+
+    /// A `Widget` refers to a parent that does not exist, or is defined after it.
+    UnknownParent { widget: String, parent: String },
+
+    /// A `Widget` sets a property with a name that is not known.
+    UnknownProperty { widget: String, property: String },
+
+    /// A `Widget` sets a property to a value that cannot be stored.
+    InvalidValue { widget: String, property: String },
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneError::Io(message) => write!(f, "unable to read scene: {}", message),
+            SceneError::Format(message) => write!(f, "unable to parse scene: {}", message),
+            SceneError::UnknownType {
+                widget,
+                widget_type,
+            } => write!(
+                f,
+                "widget '{}' has unknown widget type '{}'",
+                widget, widget_type
+            ),
+            SceneError::DuplicateName(widget) => {
+                write!(f, "widget name '{}' is already in use", widget)
+            }
+            SceneError::UnknownParent { widget, parent } => {
+                write!(f, "widget '{}' has unknown parent '{}'", widget, parent)
+            }
+            SceneError::UnknownProperty { widget, property } => {
+                write!(f, "widget '{}' has unknown property '{}'", widget, property)
+            }
+            SceneError::InvalidValue { widget, property } => write!(
+                f,
+                "widget '{}' has an invalid value for property '{}'",
+                widget, property
+            ),
+        }
+    }
+}
+
+impl Error for SceneError {}
+
+/// This is a property value as it is written in a scene file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum SceneValue {
+    Bool(bool),
+    Integer(i64),
+    Text(String),
+    List(Vec<SceneValue>),
+}
+
+impl SceneValue {
+    /// Converts the value to a `PropertyValue`, or `None` if it cannot be stored.
+    fn to_property_value(&self) -> Option<PropertyValue> {
+        match self {
+            SceneValue::Bool(value) => Some(PropertyValue::Bool(*value)),
+            SceneValue::Integer(value) => i32::try_from(*value).ok().map(PropertyValue::Integer),
+            SceneValue::Text(value) => Some(PropertyValue::String(value.clone())),
+            SceneValue::List(values) => values
+                .iter()
+                .map(SceneValue::to_property_value)
+                .collect::<Option<Vec<_>>>()
+                .map(PropertyValue::List),
+        }
+    }
+}
+
+/// This is the description of a single `Widget` in a scene file.  If no `parent` is given, the
+/// `Widget` is added to the root `Widget`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SceneWidget {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub widget_type: String,
+    #[serde(default)]
+    pub parent: Option<String>,
+    #[serde(default)]
+    pub properties: BTreeMap<String, SceneValue>,
+}
+
+/// This is the description of a scene, which is a list of `Widget`s that are added to a
+/// `WidgetCache` in the order in which they are defined.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SceneDescription {
+    #[serde(default)]
+    pub widgets: Vec<SceneWidget>,
+}
+
+/// This is the implementation of the scene loader.  A scene is fully validated before any of its
+/// `Widget`s are added, so a `WidgetCache` is never left partially populated by a scene that
+/// contains errors.
+impl SceneDescription {
+    /// Parses a scene from text in the given `SceneFormat`.
+    pub fn from_str(text: &str, format: SceneFormat) -> Result<Self, SceneError> {
+        match format {
+            SceneFormat::Json => {
+                serde_json::from_str(text).map_err(|e| SceneError::Format(e.to_string()))
+            }
+            SceneFormat::Ron => ron::from_str(text).map_err(|e| SceneError::Format(e.to_string())),
+            SceneFormat::Toml => {
+                toml::from_str(text).map_err(|e| SceneError::Format(e.to_string()))
+            }
+        }
+    }
+
+    /// Reads and parses a scene file, determining its `SceneFormat` by its extension.
+    pub fn from_file(path: &Path) -> Result<Self, SceneError> {
+        let format = SceneFormat::from_path(path).ok_or_else(|| {
+            SceneError::Io(format!("unknown scene format for {}", path.display()))
+        })?;
+        let text = fs::read_to_string(path).map_err(|e| SceneError::Io(e.to_string()))?;
+
+        SceneDescription::from_str(&text, format)
+    }
+
+    /// Adds all of the `Widget`s in the scene to the `WidgetCache`, constructing them through
+    /// the `WidgetRegistry`.  Returns the IDs of the added `Widget`s, in the order they were
+    /// defined.
+    pub fn build(
+        &self,
+        cache: &mut WidgetCache,
+        registry: &WidgetRegistry,
+    ) -> Result<Vec<u32>, SceneError> {
+        let properties = self.validate(registry, |name| find_in_cache(cache, name).is_some())?;
+        let scene_names: HashMap<&str, usize> = self
+            .widgets
+            .iter()
+            .enumerate()
+            .map(|(position, widget)| (&*widget.name, position))
+            .collect();
+        let mut widget_ids: Vec<u32> = Vec::new();
+
+        for (widget, widget_properties) in self.widgets.iter().zip(properties) {
+            let parent_id = match &widget.parent {
+                Some(parent) => match scene_names.get(&**parent) {
+                    Some(position) => widget_ids[*position],
+                    None => find_in_cache(cache, parent).unwrap(),
+                },
+                None => 0,
+            };
+            let mut new_widget = registry.create(&widget.widget_type).unwrap();
+
+            for (property_key, property_value) in widget_properties {
+                new_widget.properties().set(property_key, property_value);
+            }
+
+            widget_ids.push(cache.add(new_widget, widget.name.clone(), parent_id));
+        }
+
+        Ok(widget_ids)
+    }
+
+    /// Checks that every `Widget` in the scene has a registered type, a unique name, a known
+    /// parent, and known properties with values that can be stored, converting the properties to
+    /// their keys and values.  `exists` returns whether or not a `Widget` with the given name is
+    /// already in the `WidgetCache`.
+    fn validate(
+        &self,
+        registry: &WidgetRegistry,
+        exists: impl Fn(&str) -> bool,
+    ) -> Result<Vec<Vec<(u32, PropertyValue)>>, SceneError> {
+        let mut properties: Vec<Vec<(u32, PropertyValue)>> = Vec::new();
+        let mut scene_names: HashSet<&str> = HashSet::new();
+
+        for widget in &self.widgets {
+            if !registry.contains(&widget.widget_type) {
+                return Err(SceneError::UnknownType {
+                    widget: widget.name.clone(),
+                    widget_type: widget.widget_type.clone(),
+                });
+            }
+
+            if exists(&widget.name) || scene_names.contains(&*widget.name) {
+                return Err(SceneError::DuplicateName(widget.name.clone()));
+            }
+
+            if let Some(parent) = &widget.parent {
+                if !exists(parent) && !scene_names.contains(&**parent) {
+                    return Err(SceneError::UnknownParent {
+                        widget: widget.name.clone(),
+                        parent: parent.clone(),
+                    });
+                }
+            }
+
+            properties.push(
+                widget
+                    .properties
+                    .iter()
+                    .map(|(name, value)| {
+                        let key =
+                            property_key(name).ok_or_else(|| SceneError::UnknownProperty {
+                                widget: widget.name.clone(),
+                                property: name.clone(),
+                            })?;
+                        let property_value = value
+                            .to_property_value()
+                            .filter(|property_value| {
+                                property_descriptor(key).map_or(true, |descriptor| {
+                                    descriptor.kind.accepts(property_value)
+                                })
+                            })
+                            .ok_or_else(|| SceneError::InvalidValue {
+                                widget: widget.name.clone(),
+                                property: name.clone(),
+                            })?;
+
+                        Ok((key, property_value))
+                    })
+                    .collect::<Result<_, SceneError>>()?,
+            );
+
+            scene_names.insert(&widget.name);
+        }
+
+        Ok(properties)
+    }
+}
+
+/// Retrieves the ID of the `Widget` with the given name in the cache, or `None` if there is no
+/// such `Widget`.  `Widget`s that have been detached by undoing their addition are not found.
+fn find_in_cache(cache: &WidgetCache, name: &str) -> Option<u32> {
+    (0..cache.size())
+        .find(|widget_id| !cache.is_detached(*widget_id) && cache.get_name_of(*widget_id) == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::properties::PROPERTY_BORDER_WIDTH;
+
+    fn validate(
+        json: &str,
+        existing: &[&str],
+    ) -> Result<Vec<Vec<(u32, PropertyValue)>>, SceneError> {
+        SceneDescription::from_str(json, SceneFormat::Json)
+            .unwrap()
+            .validate(&WidgetRegistry::default(), |name| existing.contains(&name))
+    }
+
+    #[test]
+    fn validate_converts_properties() {
+        let properties = validate(
+            r#"{"widgets": [
+                {
+                    "name": "box",
+                    "type": "vbox",
+                    "parent": "root",
+                    "properties": {"border_width": 2}
+                },
+                {"name": "child", "type": "base", "parent": "box"}
+            ]}"#,
+            &["root"],
+        );
+
+        assert_eq!(
+            properties,
+            Ok(vec![
+                vec![(PROPERTY_BORDER_WIDTH, PropertyValue::Integer(2))],
+                Vec::new(),
+            ])
+        );
+    }
+
+    #[test]
+    fn validate_rejects_unknown_types() {
+        assert_eq!(
+            validate(r#"{"widgets": [{"name": "a", "type": "slider"}]}"#, &[]),
+            Err(SceneError::UnknownType {
+                widget: String::from("a"),
+                widget_type: String::from("slider"),
+            })
+        );
+    }
+
+    #[test]
+    fn validate_rejects_duplicate_names() {
+        assert_eq!(
+            validate(r#"{"widgets": [{"name": "a", "type": "base"}]}"#, &["a"]),
+            Err(SceneError::DuplicateName(String::from("a")))
+        );
+    }
+
+    #[test]
+    fn validate_rejects_unknown_properties() {
+        assert_eq!(
+            validate(
+                r#"{"widgets": [{"name": "a", "type": "base", "properties": {"colour": "red"}}]}"#,
+                &[]
+            ),
+            Err(SceneError::UnknownProperty {
+                widget: String::from("a"),
+                property: String::from("colour"),
+            })
+        );
+    }
+
+    #[test]
+    fn validate_rejects_values_of_the_wrong_kind() {
+        let invalid = |property: &str| {
+            Err(SceneError::InvalidValue {
+                widget: String::from("a"),
+                property: String::from(property),
+            })
+        };
+
+        assert_eq!(
+            validate(
+                r#"{"widgets": [{"name": "a", "type": "base", "properties": {"main_color": 5}}]}"#,
+                &[]
+            ),
+            invalid("main_color")
+        );
+        assert_eq!(
+            validate(
+                r#"{"widgets": [{"name": "a", "type": "base", "properties": {"hidden": 1}}]}"#,
+                &[]
+            ),
+            invalid("hidden")
+        );
+        assert_eq!(
+            validate(
+                r#"{"widgets": [
+                    {"name": "a", "type": "base", "properties": {"border_width": 4294967296}}
+                ]}"#,
+                &[]
+            ),
+            invalid("border_width")
+        );
+    }
+
+    #[test]
+    fn validate_rejects_missing_parents() {
+        let missing = Err(SceneError::UnknownParent {
+            widget: String::from("a"),
+            parent: String::from("b"),
+        });
+
+        assert_eq!(
+            validate(
+                r#"{"widgets": [{"name": "a", "type": "base", "parent": "b"}]}"#,
+                &[]
+            ),
+            missing
+        );
+        assert_eq!(
+            validate(
+                r#"{"widgets": [
+                    {"name": "a", "type": "base", "parent": "b"},
+                    {"name": "b", "type": "base"}
+                ]}"#,
+                &[]
+            ),
+            missing
+        );
+    }
+}
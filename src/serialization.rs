@@ -0,0 +1,441 @@
+// Pushrod Widgets
+// Serialization
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::caches::WidgetCache;
+use crate::properties::{is_transient, WidgetProperties};
+use crate::widget::Widget;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+
+/// This is the text format used when a `WidgetCache` is exported or imported.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TreeFormat {
+    Json,
+    Ron,
+}
+
+/// This is an error that is returned when a `WidgetCache` cannot be exported or imported.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TreeError {
+    /// The text could not be written or read in the requested `TreeFormat`.
+    Format(String),
+
+    /// The tree contains a `Widget` type that the factory could not construct.
+    UnknownWidgetType(String),
+
+    /// A `Widget` in the tree refers to a parent that has not been defined before it.
+    InvalidParent { widget_id: u32, parent_id: u32 },
+
+    /// The tree does not contain a root `Widget`.
+    MissingRoot,
+
+    /// A `Widget` in the tree is stored out of order, or with a gap before it, so its ID does not
+    /// match its position in the tree.
+    InvalidId { index: u32, widget_id: u32 },
+
+    /// More than one `Widget` in the tree has the same name.
+    DuplicateName(String),
+
+    /// The children listed for a `Widget` do not match the `Widget`s that name it as their parent.
+    InvalidChildren { widget_id: u32 },
+}
+
+impl fmt::Display for TreeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TreeError::Format(message) => write!(f, "format error: {}", message),
+            TreeError::UnknownWidgetType(widget_type) => {
+                write!(f, "unknown widget type '{}'", widget_type)
+            }
+            TreeError::InvalidParent {
+                widget_id,
+                parent_id,
+            } => write!(
+                f,
+                "widget {} refers to undefined parent {}",
+                widget_id, parent_id
+            ),
+            TreeError::MissingRoot => write!(f, "tree does not contain a root widget"),
+            TreeError::InvalidId { index, widget_id } => write!(
+                f,
+                "widget {} is stored at position {} in the tree",
+                widget_id, index
+            ),
+            TreeError::DuplicateName(name) => write!(f, "widget name '{}' is used twice", name),
+            TreeError::InvalidChildren { widget_id } => write!(
+                f,
+                "children of widget {} do not match their parents",
+                widget_id
+            ),
+        }
+    }
+}
+
+impl Error for TreeError {}
+
+/// This is the stored form of a single `Widget` in a `WidgetTree`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WidgetRecord {
+    pub id: u32,
+    pub name: String,
+    pub widget_type: String,
+    pub parent: u32,
+    pub children: Vec<u32>,
+    pub properties: WidgetProperties,
+}
+
+/// This is the stored form of a `WidgetCache`.  `Widget`s are stored in ID order, starting with
+/// the root `Widget`, so that every parent is defined before its children.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WidgetTree {
+    pub widgets: Vec<WidgetRecord>,
+}
+
+/// This is the implementation of the `WidgetTree`, which converts it to and from text.
+impl WidgetTree {
+    /// Writes the tree as text in the given `TreeFormat`.
+    pub fn to_string(&self, format: TreeFormat) -> Result<String, TreeError> {
+        match format {
+            TreeFormat::Json => {
+                serde_json::to_string_pretty(self).map_err(|e| TreeError::Format(e.to_string()))
+            }
+            TreeFormat::Ron => ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+                .map_err(|e| TreeError::Format(e.to_string())),
+        }
+    }
+
+    /// Checks that the tree describes a valid set of `Widget`s: the root `Widget` comes first,
+    /// each `Widget` is stored at the position matching its ID, every parent is defined before its
+    /// children, names are unique, and the children listed for each `Widget` are the `Widget`s
+    /// that name it as their parent, in order.
+    pub fn validate(&self) -> Result<(), TreeError> {
+        if self.widgets.is_empty() {
+            return Err(TreeError::MissingRoot);
+        }
+
+        let mut names = HashSet::new();
+
+        for (index, record) in self.widgets.iter().enumerate() {
+            let index = index as u32;
+
+            if record.id != index {
+                return Err(TreeError::InvalidId {
+                    index,
+                    widget_id: record.id,
+                });
+            }
+
+            if (index == 0 && record.parent != 0) || (index > 0 && record.parent >= index) {
+                return Err(TreeError::InvalidParent {
+                    widget_id: record.id,
+                    parent_id: record.parent,
+                });
+            }
+
+            if !names.insert(record.name.as_str()) {
+                return Err(TreeError::DuplicateName(record.name.clone()));
+            }
+        }
+
+        for record in &self.widgets {
+            let children: Vec<u32> = self
+                .widgets
+                .iter()
+                .skip(1)
+                .filter(|child| child.parent == record.id)
+                .map(|child| child.id)
+                .collect();
+
+            if children != record.children {
+                return Err(TreeError::InvalidChildren {
+                    widget_id: record.id,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads a tree from text in the given `TreeFormat`.
+    pub fn from_str(text: &str, format: TreeFormat) -> Result<Self, TreeError> {
+        match format {
+            TreeFormat::Json => {
+                serde_json::from_str(text).map_err(|e| TreeError::Format(e.to_string()))
+            }
+            TreeFormat::Ron => ron::from_str(text).map_err(|e| TreeError::Format(e.to_string())),
+        }
+    }
+}
+
+/// This is the implementation of the export and import functions for the `WidgetCache`.
+impl WidgetCache {
+    /// Exports every `Widget` in the cache - its name, parent and children, type and properties -
+    /// as a `WidgetTree`.  `Widget`s whose addition has been undone are left out, and the
+    /// remaining `Widget`s are renumbered so that their IDs stay contiguous.  Transient state,
+    /// such as the hovered and focused flags, is not exported.
+    pub fn export_tree(&self) -> WidgetTree {
+        let widgets = (0..self.size())
+            .filter(|widget_id| !self.is_detached(*widget_id))
+            .map(|widget_id| {
+                let mut widget = self.get(widget_id).borrow_mut();
+
+                WidgetRecord {
+                    id: widget_id,
+                    name: self.get_name_of(widget_id).to_string(),
+                    widget_type: widget.widget_type().to_string(),
+                    parent: self.get_parent_of(widget_id),
+                    children: self.get_children_of(widget_id),
+                    properties: persistent_properties(widget.properties()),
+                }
+            })
+            .collect();
+
+        WidgetTree {
+            widgets: renumber(widgets),
+        }
+    }
+
+    /// Exports every `Widget` in the cache as text in the given `TreeFormat`.
+    pub fn export(&self, format: TreeFormat) -> Result<String, TreeError> {
+        self.export_tree().to_string(format)
+    }
+
+    /// Creates a new `WidgetCache` from a `WidgetTree`, which is checked with
+    /// `WidgetTree::validate` before any `Widget` is created.  The root `Widget` is always created
+    /// as a `BaseWidget`, and receives the properties stored for the root.  All other `Widget`s
+    /// are constructed by calling the `factory` with the stored `Widget` type, which returns
+    /// `None` if the type is not known.
+    pub fn import_tree(
+        tree: &WidgetTree,
+        factory: &dyn Fn(&str) -> Option<Box<dyn Widget>>,
+    ) -> Result<Self, TreeError> {
+        tree.validate()?;
+
+        let widgets = tree
+            .widgets
+            .iter()
+            .skip(1)
+            .map(|record| {
+                let mut widget = factory(&record.widget_type)
+                    .ok_or_else(|| TreeError::UnknownWidgetType(record.widget_type.clone()))?;
+
+                *widget.properties() = record.properties.clone();
+                Ok((widget, record))
+            })
+            .collect::<Result<Vec<_>, TreeError>>()?;
+        let root = &tree.widgets[0];
+        let bounds = root.properties.get_bounds();
+        let mut cache = WidgetCache::new(bounds.0, bounds.1);
+
+        *cache.get(0).borrow_mut().properties() = root.properties.clone();
+
+        for (widget, record) in widgets {
+            cache.add(widget, record.name.clone(), record.parent);
+        }
+
+        Ok(cache)
+    }
+
+    /// Creates a new `WidgetCache` from text in the given `TreeFormat`.  See `import_tree` for
+    /// details on how `Widget`s are constructed.
+    pub fn import(
+        text: &str,
+        format: TreeFormat,
+        factory: &dyn Fn(&str) -> Option<Box<dyn Widget>>,
+    ) -> Result<Self, TreeError> {
+        WidgetCache::import_tree(&WidgetTree::from_str(text, format)?, factory)
+    }
+}
+
+/// Returns a copy of the properties set on a `Widget`, without any transient properties.
+fn persistent_properties(properties: &WidgetProperties) -> WidgetProperties {
+    let mut persistent = properties.clone();
+    let transient: Vec<u32> = properties
+        .iter()
+        .map(|(property_key, _)| *property_key)
+        .filter(|property_key| is_transient(*property_key))
+        .collect();
+
+    for property_key in transient {
+        persistent.delete(property_key);
+    }
+
+    persistent
+}
+
+/// Renumbers records so that their IDs match their positions, remapping parents and children to
+/// the new IDs.  Children that are not among the records are dropped.
+fn renumber(mut widgets: Vec<WidgetRecord>) -> Vec<WidgetRecord> {
+    let ids: HashMap<u32, u32> = widgets
+        .iter()
+        .enumerate()
+        .map(|(index, record)| (record.id, index as u32))
+        .collect();
+
+    for record in widgets.iter_mut() {
+        record.id = ids[&record.id];
+        record.parent = ids.get(&record.parent).copied().unwrap_or(0);
+        record.children = record
+            .children
+            .iter()
+            .filter_map(|child_id| ids.get(child_id).copied())
+            .collect();
+    }
+
+    widgets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::properties::{
+        PROPERTY_BORDER_WIDTH, PROPERTY_FOCUSED, PROPERTY_HOVERED, PROPERTY_INVALIDATED,
+    };
+
+    fn record(id: u32, name: &str, parent: u32, children: Vec<u32>) -> WidgetRecord {
+        WidgetRecord {
+            id,
+            name: String::from(name),
+            widget_type: String::from("base"),
+            parent,
+            children,
+            properties: WidgetProperties::default(),
+        }
+    }
+
+    fn tree(widgets: Vec<WidgetRecord>) -> WidgetTree {
+        WidgetTree { widgets }
+    }
+
+    #[test]
+    fn validate_accepts_consistent_tree() {
+        let widgets = tree(vec![
+            record(0, "root", 0, vec![1, 2]),
+            record(1, "panel", 0, vec![3]),
+            record(2, "footer", 0, vec![]),
+            record(3, "label", 1, vec![]),
+        ]);
+
+        assert_eq!(widgets.validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_rejects_empty_tree() {
+        assert_eq!(tree(vec![]).validate(), Err(TreeError::MissingRoot));
+    }
+
+    #[test]
+    fn validate_rejects_ids_out_of_order() {
+        let widgets = tree(vec![
+            record(0, "root", 0, vec![2]),
+            record(2, "panel", 0, vec![]),
+        ]);
+
+        assert_eq!(
+            widgets.validate(),
+            Err(TreeError::InvalidId {
+                index: 1,
+                widget_id: 2
+            })
+        );
+    }
+
+    #[test]
+    fn validate_rejects_parent_defined_later() {
+        let widgets = tree(vec![
+            record(0, "root", 0, vec![]),
+            record(1, "label", 2, vec![]),
+            record(2, "panel", 0, vec![1]),
+        ]);
+
+        assert_eq!(
+            widgets.validate(),
+            Err(TreeError::InvalidParent {
+                widget_id: 1,
+                parent_id: 2
+            })
+        );
+    }
+
+    #[test]
+    fn validate_rejects_duplicate_names() {
+        let widgets = tree(vec![
+            record(0, "root", 0, vec![1, 2]),
+            record(1, "button", 0, vec![]),
+            record(2, "button", 0, vec![]),
+        ]);
+
+        assert_eq!(
+            widgets.validate(),
+            Err(TreeError::DuplicateName(String::from("button")))
+        );
+    }
+
+    #[test]
+    fn validate_rejects_mismatched_children() {
+        let widgets = tree(vec![
+            record(0, "root", 0, vec![1]),
+            record(1, "panel", 0, vec![]),
+            record(2, "label", 1, vec![]),
+        ]);
+
+        assert_eq!(
+            widgets.validate(),
+            Err(TreeError::InvalidChildren { widget_id: 1 })
+        );
+    }
+
+    #[test]
+    fn renumber_closes_gaps_left_by_detached_widgets() {
+        let widgets = renumber(vec![
+            record(0, "root", 0, vec![1, 4]),
+            record(1, "panel", 0, vec![3]),
+            record(3, "label", 1, vec![]),
+            record(4, "footer", 0, vec![]),
+        ]);
+        let expected = tree(vec![
+            record(0, "root", 0, vec![1, 3]),
+            record(1, "panel", 0, vec![2]),
+            record(2, "label", 1, vec![]),
+            record(3, "footer", 0, vec![]),
+        ]);
+
+        for (widget, expected) in widgets.iter().zip(expected.widgets.iter()) {
+            assert_eq!(widget.id, expected.id);
+            assert_eq!(widget.parent, expected.parent);
+            assert_eq!(widget.children, expected.children);
+        }
+
+        assert_eq!(tree(widgets).validate(), Ok(()));
+    }
+
+    #[test]
+    fn persistent_properties_skips_transient_keys() {
+        let mut properties = WidgetProperties::default();
+
+        properties.set_value(PROPERTY_BORDER_WIDTH, 2);
+        properties.set_bool(PROPERTY_HOVERED);
+        properties.set_bool(PROPERTY_FOCUSED);
+
+        let persistent = persistent_properties(&properties);
+
+        assert!(persistent.key_set(PROPERTY_BORDER_WIDTH));
+        assert!(!persistent.key_set(PROPERTY_INVALIDATED));
+        assert!(!persistent.key_set(PROPERTY_HOVERED));
+        assert!(!persistent.key_set(PROPERTY_FOCUSED));
+    }
+}
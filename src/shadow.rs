@@ -0,0 +1,249 @@
+// Pushrod Widgets
+// Shadow
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::border::inside_rounded_rect;
+use crate::properties::{
+    WidgetProperties, PROPERTY_BORDER_RADIUS, PROPERTY_SHADOW_BLUR, PROPERTY_SHADOW_COLOR,
+    PROPERTY_SHADOW_OFFSET, PROPERTY_SHADOW_SPREAD,
+};
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+
+/// This is a shadow, drawn beneath a `Widget` by the `WidgetCache`.  The shadow has the shape of
+/// the `Widget` (including its rounded corners), grown by `spread` pixels on each side, moved by
+/// `offset`, and blurred over `blur` pixels.  An outer glow is a shadow without an offset, using a
+/// light color.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Shadow {
+    pub offset: (i32, i32),
+    pub blur: u32,
+    pub spread: i32,
+    pub color: Color,
+    pub radius: u32,
+}
+
+/// This is the implementation of the `Shadow`.
+impl Shadow {
+    /// Reads a `Shadow` from the properties of a `Widget`.  Returns `None` if
+    /// `PROPERTY_SHADOW_COLOR` has not been set, or is fully transparent, as the `Widget` has no
+    /// shadow.
+    pub fn from_properties(properties: &WidgetProperties) -> Option<Self> {
+        let color = properties.try_get_color(PROPERTY_SHADOW_COLOR).ok()?;
+
+        if color.a == 0 {
+            return None;
+        }
+
+        Some(Self {
+            offset: properties
+                .try_get_point(PROPERTY_SHADOW_OFFSET)
+                .unwrap_or((0, 0)),
+            blur: properties.get_value(PROPERTY_SHADOW_BLUR).max(0) as u32,
+            spread: properties.get_value(PROPERTY_SHADOW_SPREAD),
+            color,
+            radius: properties.get_value(PROPERTY_BORDER_RADIUS).max(0) as u32,
+        })
+    }
+
+    /// Returns the area covered by the shadow of a `Widget` drawn at `widget_rect`, including the
+    /// blur, or `None` if the shadow is empty.
+    pub fn bounds(&self, widget_rect: Rect) -> Option<Rect> {
+        let (width, height) = self.size(widget_rect.width(), widget_rect.height())?;
+        let margin = self.spread + self.blur as i32;
+
+        Some(Rect::new(
+            widget_rect.x() + self.offset.0 - margin,
+            widget_rect.y() + self.offset.1 - margin,
+            width,
+            height,
+        ))
+    }
+
+    /// Returns the size of the shadow of a `Widget` of the given size, including the blur, or
+    /// `None` if the shadow is empty.
+    pub fn size(&self, width: u32, height: u32) -> Option<(u32, u32)> {
+        let grow = |length: u32| length as i32 + 2 * self.spread;
+        let (shape_width, shape_height) = (grow(width), grow(height));
+
+        if shape_width <= 0 || shape_height <= 0 {
+            None
+        } else {
+            Some((
+                (shape_width as u32) + 2 * self.blur,
+                (shape_height as u32) + 2 * self.blur,
+            ))
+        }
+    }
+
+    /// Renders the shadow of a `Widget` of the given size, as rows of RGBA bytes, along with the
+    /// size of the rendered shadow.  Returns `None` if the shadow is empty.
+    pub fn pixels(&self, width: u32, height: u32) -> Option<(Vec<u8>, (u32, u32))> {
+        let (shadow_width, shadow_height) = self.size(width, height)?;
+        let blur = f64::from(self.blur);
+        let shape = (
+            blur,
+            blur,
+            f64::from(shadow_width) - blur,
+            f64::from(shadow_height) - blur,
+        );
+        let radius = if self.radius > 0 {
+            (self.radius as i32 + self.spread).max(0) as f64
+        } else {
+            0.0
+        }
+        .min((shape.2 - shape.0) / 2.0)
+        .min((shape.3 - shape.1) / 2.0);
+        let mut mask: Vec<f64> = (0..shadow_height)
+            .flat_map(|y| {
+                (0..shadow_width).map(move |x| {
+                    let (px, py) = (f64::from(x) + 0.5, f64::from(y) + 0.5);
+
+                    if inside_rounded_rect(px, py, shape, [(radius, radius); 4]) {
+                        1.0
+                    } else {
+                        0.0
+                    }
+                })
+            })
+            .collect();
+
+        // Three passes of a box blur approximate a gaussian blur over the `blur` distance.
+        if self.blur > 0 {
+            let box_radius = (self.blur / 3).max(1) as usize;
+
+            for _ in 0..3 {
+                box_blur(
+                    &mut mask,
+                    shadow_width as usize,
+                    shadow_height as usize,
+                    box_radius,
+                );
+            }
+        }
+
+        let pixels = mask
+            .iter()
+            .flat_map(|coverage| {
+                let alpha = (f64::from(self.color.a) * coverage).round() as u8;
+
+                [self.color.r, self.color.g, self.color.b, alpha]
+            })
+            .collect();
+
+        Some((pixels, (shadow_width, shadow_height)))
+    }
+}
+
+/// Blurs a mask horizontally, then vertically, averaging each value with the `radius` values on
+/// either side of it.
+fn box_blur(mask: &mut [f64], width: usize, height: usize, radius: usize) {
+    let blur_line = |values: Vec<f64>| -> Vec<f64> {
+        let length = values.len();
+        let mut sums = vec![0.0; length + 1];
+
+        for (index, value) in values.iter().enumerate() {
+            sums[index + 1] = sums[index] + value;
+        }
+
+        (0..length)
+            .map(|index| {
+                let start = index.saturating_sub(radius);
+                let end = (index + radius + 1).min(length);
+
+                (sums[end] - sums[start]) / (2 * radius + 1) as f64
+            })
+            .collect()
+    };
+
+    for y in 0..height {
+        let row = blur_line(mask[y * width..(y + 1) * width].to_vec());
+
+        mask[y * width..(y + 1) * width].copy_from_slice(&row);
+    }
+
+    for x in 0..width {
+        let column = blur_line((0..height).map(|y| mask[y * width + x]).collect());
+
+        for (y, value) in column.into_iter().enumerate() {
+            mask[y * width + x] = value;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shadow(offset: (i32, i32), blur: u32, spread: i32, radius: u32) -> Shadow {
+        Shadow {
+            offset,
+            blur,
+            spread,
+            color: Color::RGBA(0, 0, 0, 255),
+            radius,
+        }
+    }
+
+    fn alpha(pixels: &[u8], width: u32, x: u32, y: u32) -> u8 {
+        pixels[((y * width + x) * 4 + 3) as usize]
+    }
+
+    #[test]
+    fn negative_spread_can_collapse_the_shadow() {
+        let collapsed = shadow((0, 0), 2, -5, 0);
+
+        assert_eq!(collapsed.size(10, 10), None);
+        assert_eq!(collapsed.bounds(Rect::new(0, 0, 10, 10)), None);
+        assert!(collapsed.pixels(10, 10).is_none());
+        assert_eq!(shadow((0, 0), 2, -4, 0).size(10, 10), Some((6, 6)));
+    }
+
+    #[test]
+    fn bounds_are_offset_and_grown_by_the_spread_and_blur() {
+        let bounds = shadow((3, 4), 2, 1, 0).bounds(Rect::new(10, 20, 30, 40));
+
+        assert_eq!(bounds, Some(Rect::new(10, 21, 36, 46)));
+    }
+
+    #[test]
+    fn blur_fades_the_edges_and_keeps_the_middle_solid() {
+        let (pixels, (width, height)) = shadow((0, 0), 6, 0, 0).pixels(20, 20).unwrap();
+        let middle = height / 2;
+        let row: Vec<u8> = (0..=width / 2)
+            .map(|x| alpha(&pixels, width, x, middle))
+            .collect();
+
+        assert_eq!((width, height), (32, 32));
+        assert_eq!(alpha(&pixels, width, width / 2, middle), 255);
+        assert!(row[0] < 128);
+        assert!(row.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(alpha(&pixels, width, 0, 0) < row[0]);
+    }
+
+    #[test]
+    fn spread_grows_the_corner_radius() {
+        let (pixels, (width, _)) = shadow((0, 0), 0, 2, 4).pixels(10, 10).unwrap();
+
+        assert_eq!(width, 14);
+        assert_eq!(alpha(&pixels, width, 1, 1), 0);
+        assert_eq!(alpha(&pixels, width, 2, 2), 255);
+        assert_eq!(alpha(&pixels, width, 7, 7), 255);
+
+        let (pixels, (width, _)) = shadow((0, 0), 0, -3, 2).pixels(10, 10).unwrap();
+
+        assert_eq!(width, 4);
+        assert_eq!(alpha(&pixels, width, 0, 0), 255);
+    }
+}
@@ -13,21 +13,68 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use sdl2::render::{Canvas, Texture};
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Canvas, Texture};
 use sdl2::video::Window;
 
+use crate::background::{Gradient, ImageMode};
+use crate::border::{Border, BorderStyle};
 use crate::caches::TextureCache;
-use crate::properties::{WidgetProperties, PROPERTY_MAIN_COLOR, PROPERTY_BORDER_COLOR, PROPERTY_BORDER_WIDTH};
+use crate::properties::{
+    Sides, WidgetProperties, PROPERTY_BACKGROUND_GRADIENT, PROPERTY_BACKGROUND_IMAGE,
+    PROPERTY_BACKGROUND_IMAGE_MODE, PROPERTY_FOCUSED, PROPERTY_FOCUS_RING_COLOR,
+    PROPERTY_FOCUS_RING_WIDTH, PROPERTY_HOVERED, PROPERTY_HOVER_COLOR, PROPERTY_MAIN_COLOR,
+};
 use crate::texture_store::TextureStore;
 use crate::widget::Widget;
-use sdl2::pixels::Color;
-use sdl2::rect::Rect;
+use sdl2::pixels::{Color, PixelFormatEnum};
 
 /// Base Widget.
 #[derive(Default)]
 pub struct BaseWidget {
     texture_store: TextureStore,
     properties: WidgetProperties,
+    gradient_key: (String, (u32, u32)),
+    gradient: Option<Texture>,
+}
+
+/// This is the implementation of the `BaseWidget`.
+impl BaseWidget {
+    /// Renders the background gradient into its own texture, if the gradient text or the bounds
+    /// of the `Widget` have changed since it was last rendered.  The previous texture is
+    /// destroyed, as textures are not freed when they are dropped.
+    fn update_gradient(&mut self, c: &mut Canvas<Window>, text: String, bounds: (u32, u32)) {
+        let key = (text, bounds);
+
+        if key == self.gradient_key {
+            return;
+        }
+
+        if let Some(texture) = self.gradient.take() {
+            unsafe { texture.destroy() };
+        }
+
+        self.gradient = Gradient::parse(&key.0)
+            .ok()
+            .filter(|_| bounds.0 > 0 && bounds.1 > 0)
+            .map(|gradient| {
+                let mut texture = c
+                    .texture_creator()
+                    .create_texture_static(PixelFormatEnum::RGBA32, bounds.0, bounds.1)
+                    .unwrap();
+
+                texture
+                    .update(
+                        None,
+                        &gradient.pixels(bounds.0, bounds.1),
+                        bounds.0 as usize * 4,
+                    )
+                    .unwrap();
+                texture.set_blend_mode(BlendMode::Blend);
+                texture
+            });
+        self.gradient_key = key;
+    }
 }
 
 /// Implementation for drawing a `BaseWidget`, with the `Widget` trait objects applied.
@@ -36,46 +83,93 @@ impl Widget for BaseWidget {
         &mut self.properties
     }
 
-    fn draw(&mut self, c: &mut Canvas<Window>, _t: &mut TextureCache) -> Option<&Texture> {
+    fn widget_type(&self) -> &'static str {
+        "base"
+    }
+
+    fn draw(&mut self, c: &mut Canvas<Window>, t: &mut TextureCache) -> Option<&Texture> {
         // ONLY update the texture if the `BaseWidget` shows that it's been invalidated.
         if self.invalidated() {
-            // This is the fill color for this Widget.
-            let base_color = self
+            // This is the fill color for this Widget, which changes while the pointer is over it.
+            let main_color = self
                 .properties
                 .get_color(PROPERTY_MAIN_COLOR, Color::RGB(255, 255, 255));
+            let base_color = if self.properties.get_bool(PROPERTY_HOVERED) {
+                self.properties.get_color(PROPERTY_HOVER_COLOR, main_color)
+            } else {
+                main_color
+            };
 
-            // This is the border paint color.
-            let border_color = self
-                .properties
-                .get_color(PROPERTY_BORDER_COLOR, Color::RGB(0, 0, 0));
+            // This is the border, with a width, color and style for each side.
+            let border = Border::from_properties(&self.properties);
             let bounds = self.properties.get_bounds();
+            let paint = border.paint(None, bounds.0, bounds.1);
 
-            // Border width
-            let border_width = self
-                .properties
-                .get_value(PROPERTY_BORDER_WIDTH);
+            // The focus ring is drawn inside the border while the Widget has the keyboard focus.
+            let ring = if self.properties.get_bool(PROPERTY_FOCUSED) {
+                focus_ring(&self.properties, &border, bounds)
+            } else {
+                Vec::new()
+            };
+
+            // The gradient is painted over the fill color, and is rendered into a texture of its
+            // own.
+            let gradient = self.properties.get_string(PROPERTY_BACKGROUND_GRADIENT);
+
+            self.update_gradient(c, gradient, bounds);
+
+            // The image is painted over the gradient, placed according to its mode.
+            let image_name = self.properties.get_string(PROPERTY_BACKGROUND_IMAGE);
+            let image = if image_name.is_empty() {
+                None
+            } else {
+                let mode = ImageMode::from_name(
+                    &self.properties.get_string(PROPERTY_BACKGROUND_IMAGE_MODE),
+                )
+                .unwrap_or(ImageMode::Stretch);
+                // An image that cannot be loaded is left out, so that the rest of the Widget is
+                // still drawn.
+                match t.get_image(c, image_name.clone()) {
+                    Ok(image) => {
+                        let query = image.query();
+
+                        Some((image, mode.placements((query.width, query.height), bounds)))
+                    }
+                    Err(error) => {
+                        eprintln!("Unable to load image {}: {}", image_name, error);
+                        None
+                    }
+                }
+            };
 
             self.texture_store
                 .create_or_resize_texture(c, bounds.0, bounds.1);
 
+            // Pixels outside of rounded corners are left transparent.
+            self.texture_store
+                .get_mut_ref()
+                .set_blend_mode(BlendMode::Blend);
+
+            let gradient = &self.gradient;
+
             c.with_texture_canvas(self.texture_store.get_mut_ref(), |texture| {
-                // Fill the texture
                 texture.set_draw_color(base_color);
                 texture.clear();
 
-                if border_width > 0 {
-                    // Draw the border with the color of the border
-                    texture.set_draw_color(border_color);
-
-                    for border_width_count in 0..border_width {
-                        texture
-                            .draw_rect(Rect::new(border_width_count,
-                                                 border_width_count,
-                                                 bounds.0 - border_width_count as u32,
-                                                 bounds.1 - border_width_count as u32))
-                            .unwrap();
+                if let Some(gradient) = gradient {
+                    texture.copy(gradient, None, None).unwrap();
+                }
+
+                if let Some((image, placements)) = &image {
+                    for placement in placements {
+                        texture.copy(image, None, *placement).unwrap();
                     }
                 }
+
+                for (color, rects) in paint.iter().chain(ring.iter()) {
+                    texture.set_draw_color(*color);
+                    texture.fill_rects(rects).unwrap();
+                }
             })
             .unwrap();
         }
@@ -83,3 +177,117 @@ impl Widget for BaseWidget {
         self.texture_store.get_optional_ref()
     }
 }
+
+/// Computes the rectangles to fill in order to paint the focus ring of a `Widget` of the given
+/// size, grouped by color.  The ring is `PROPERTY_FOCUS_RING_WIDTH` pixels wide, and is painted
+/// inside the `border`, following its rounded corners.  The pixels outside of the ring's rounded
+/// corners are left out, so that the border underneath them is left untouched.
+fn focus_ring(
+    properties: &WidgetProperties,
+    border: &Border,
+    bounds: (u32, u32),
+) -> Vec<(Color, Vec<Rect>)> {
+    let ring_width = properties.get_value(PROPERTY_FOCUS_RING_WIDTH).max(0) as u32;
+
+    if ring_width == 0 {
+        return Vec::new();
+    }
+
+    let widths = border.widths;
+    let width = bounds.0.saturating_sub(widths.left + widths.right);
+    let height = bounds.1.saturating_sub(widths.top + widths.bottom);
+
+    let thickest = widths
+        .top
+        .max(widths.right)
+        .max(widths.bottom)
+        .max(widths.left);
+    let ring = Border {
+        widths: Sides::uniform(ring_width),
+        colors: Sides::uniform(
+            properties.get_color(PROPERTY_FOCUS_RING_COLOR, Color::RGB(0, 120, 215)),
+        ),
+        radius: border.radius.saturating_sub(thickest),
+        style: BorderStyle::Solid,
+    };
+
+    ring.paint(None, width, height)
+        .into_iter()
+        .filter(|(color, _)| *color != Color::RGBA(0, 0, 0, 0))
+        .map(|(color, rects)| {
+            let rects = rects
+                .into_iter()
+                .map(|rect| {
+                    Rect::new(
+                        rect.x() + widths.left as i32,
+                        rect.y() + widths.top as i32,
+                        rect.width(),
+                        rect.height(),
+                    )
+                })
+                .collect();
+
+            (color, rects)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::properties::{PROPERTY_BORDER_RADIUS, PROPERTY_BORDER_WIDTH};
+
+    fn ring_rects(properties: &WidgetProperties, bounds: (u32, u32)) -> Vec<Rect> {
+        focus_ring(properties, &Border::from_properties(properties), bounds)
+            .into_iter()
+            .flat_map(|(_, rects)| rects)
+            .collect()
+    }
+
+    #[test]
+    fn focus_ring_uses_the_default_width() {
+        let rects = ring_rects(&WidgetProperties::default(), (20, 10));
+        let covered: u32 = rects.iter().map(|rect| rect.width() * rect.height()).sum();
+
+        assert_eq!(covered, 20 * 10 - 16 * 6);
+    }
+
+    #[test]
+    fn focus_ring_is_inset_by_the_border() {
+        let mut properties = WidgetProperties::default();
+
+        properties.set_value(PROPERTY_BORDER_WIDTH, 3);
+
+        let rects = ring_rects(&properties, (20, 10));
+        let inner = Rect::new(3, 3, 14, 4);
+
+        assert!(!rects.is_empty());
+        assert!(rects.iter().all(|rect| inner.contains_rect(*rect)));
+    }
+
+    #[test]
+    fn focus_ring_leaves_the_rounded_corners_of_the_border_untouched() {
+        let mut properties = WidgetProperties::default();
+
+        properties.set_value(PROPERTY_BORDER_WIDTH, 2);
+        properties.set_value(PROPERTY_BORDER_RADIUS, 6);
+
+        let groups = focus_ring(&properties, &Border::from_properties(&properties), (20, 12));
+        let rects: Vec<Rect> = groups.iter().flat_map(|(_, rects)| rects.clone()).collect();
+
+        assert!(!rects.is_empty());
+        assert!(groups.iter().all(|(color, _)| color.a == 255));
+        assert!(rects
+            .iter()
+            .all(|rect| !rect.contains_point((2, 2)) && !rect.contains_point((17, 9))));
+    }
+
+    #[test]
+    fn focus_ring_can_be_turned_off() {
+        let mut properties = WidgetProperties::default();
+
+        properties.set_value(PROPERTY_FOCUS_RING_WIDTH, 0);
+
+        assert!(ring_rects(&properties, (20, 10)).is_empty());
+    }
+}
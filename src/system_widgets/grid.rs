@@ -0,0 +1,76 @@
+// Pushrod Widgets
+// Grid
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use sdl2::rect::Rect;
+use sdl2::render::{Canvas, Texture};
+use sdl2::video::Window;
+
+use crate::caches::TextureCache;
+use crate::layout::{arrange_grid, container_size, measure_grid, Alignment, LayoutItem, Size};
+use crate::properties::{WidgetProperties, PROPERTY_GRID_COLUMNS, PROPERTY_SPACING};
+use crate::system_widgets::base_widget::BaseWidget;
+use crate::widget::Widget;
+
+/// This is a container that places its children in the cells of a grid with
+/// `PROPERTY_GRID_COLUMNS` columns, separated by `PROPERTY_SPACING` pixels.  Children are placed in
+/// the cell set by their `PROPERTY_GRID_CELL`, which may span several rows and columns, or in the
+/// next free cell.  Cells that extend beyond the last column are shortened to fit.  Within their
+/// cells, children are placed according to the container's `PROPERTY_ALIGNMENT`, stretching to
+/// fill them by default.
+#[derive(Default)]
+pub struct Grid {
+    base: BaseWidget,
+}
+
+/// Implementation for drawing a `Grid`, which draws its background as a `BaseWidget` does, and
+/// arranges its children.
+impl Widget for Grid {
+    fn properties(&mut self) -> &mut WidgetProperties {
+        self.base.properties()
+    }
+
+    fn widget_type(&self) -> &'static str {
+        "grid"
+    }
+
+    fn draw(&mut self, c: &mut Canvas<Window>, t: &mut TextureCache) -> Option<&Texture> {
+        self.base.draw(c, t)
+    }
+
+    fn measure_children(&mut self, _available: Size, items: &[LayoutItem]) -> Option<Size> {
+        let properties = self.properties();
+
+        Some(container_size(
+            properties,
+            measure_grid(
+                properties.get_value(PROPERTY_GRID_COLUMNS).max(1) as u32,
+                properties.get_value(PROPERTY_SPACING),
+                items,
+            ),
+        ))
+    }
+
+    fn arrange(&mut self, area: Rect, items: &[LayoutItem]) -> Option<Vec<Rect>> {
+        let properties = self.properties();
+
+        Some(arrange_grid(
+            area,
+            properties.get_value(PROPERTY_GRID_COLUMNS).max(1) as u32,
+            properties.get_value(PROPERTY_SPACING),
+            Alignment::from_properties(properties),
+            items,
+        ))
+    }
+}
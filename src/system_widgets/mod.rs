@@ -17,3 +17,15 @@
 /// in the properties.  Custom Widgets can use the `BaseWidget` as a top-level widget for drawing
 /// a background or the bounding box, extending it later.
 pub mod base_widget;
+
+/// This is a container that places its children in a row.
+pub mod hbox;
+
+/// This is a container that places its children in a column.
+pub mod vbox;
+
+/// This is a container that places its children in the cells of a grid.
+pub mod grid;
+
+/// This is a container that places its children on top of each other.
+pub mod stack;
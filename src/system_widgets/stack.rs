@@ -0,0 +1,65 @@
+// Pushrod Widgets
+// Stack
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use sdl2::rect::Rect;
+use sdl2::render::{Canvas, Texture};
+use sdl2::video::Window;
+
+use crate::caches::TextureCache;
+use crate::layout::{arrange_stack, container_size, measure_stack, Alignment, LayoutItem, Size};
+use crate::properties::WidgetProperties;
+use crate::system_widgets::base_widget::BaseWidget;
+use crate::widget::Widget;
+
+/// This is a container that places its children on top of each other, each within its whole
+/// content area, according to the container's `PROPERTY_ALIGNMENT`.  Children are drawn in the
+/// order they were added, so only the topmost visible child is seen where they overlap; hide
+/// children with `PROPERTY_HIDDEN` to switch between them.
+#[derive(Default)]
+pub struct Stack {
+    base: BaseWidget,
+}
+
+/// Implementation for drawing a `Stack`, which draws its background as a `BaseWidget` does, and
+/// arranges its children.
+impl Widget for Stack {
+    fn properties(&mut self) -> &mut WidgetProperties {
+        self.base.properties()
+    }
+
+    fn widget_type(&self) -> &'static str {
+        "stack"
+    }
+
+    fn draw(&mut self, c: &mut Canvas<Window>, t: &mut TextureCache) -> Option<&Texture> {
+        self.base.draw(c, t)
+    }
+
+    fn measure_children(&mut self, _available: Size, items: &[LayoutItem]) -> Option<Size> {
+        let properties = self.properties();
+
+        Some(container_size(properties, measure_stack(items)))
+    }
+
+    fn arrange(&mut self, area: Rect, items: &[LayoutItem]) -> Option<Vec<Rect>> {
+        let properties = self.properties();
+
+        Some(arrange_stack(
+            area,
+            Alignment::from_properties(properties),
+            items,
+        ))
+    }
+}
@@ -0,0 +1,74 @@
+// Pushrod Widgets
+// Vertical Box
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use sdl2::rect::Rect;
+use sdl2::render::{Canvas, Texture};
+use sdl2::video::Window;
+
+use crate::caches::TextureCache;
+use crate::layout::{arrange_box, container_size, measure_box, Alignment, Axis, LayoutItem, Size};
+use crate::properties::{WidgetProperties, PROPERTY_SPACING};
+use crate::system_widgets::base_widget::BaseWidget;
+use crate::widget::Widget;
+
+/// This is a container that places its children in a column, from top to bottom, separated by
+/// `PROPERTY_SPACING` pixels.  Children keep their preferred height, and share any space left over
+/// by their `PROPERTY_STRETCH` factors.  Horizontally, children are placed according to the
+/// container's `PROPERTY_ALIGNMENT`, stretching to its width by default.
+#[derive(Default)]
+pub struct VBox {
+    base: BaseWidget,
+}
+
+/// Implementation for drawing a `VBox`, which draws its background as a `BaseWidget` does, and
+/// arranges its children.
+impl Widget for VBox {
+    fn properties(&mut self) -> &mut WidgetProperties {
+        self.base.properties()
+    }
+
+    fn widget_type(&self) -> &'static str {
+        "vbox"
+    }
+
+    fn draw(&mut self, c: &mut Canvas<Window>, t: &mut TextureCache) -> Option<&Texture> {
+        self.base.draw(c, t)
+    }
+
+    fn measure_children(&mut self, _available: Size, items: &[LayoutItem]) -> Option<Size> {
+        let properties = self.properties();
+
+        Some(container_size(
+            properties,
+            measure_box(
+                Axis::Vertical,
+                properties.get_value(PROPERTY_SPACING),
+                items,
+            ),
+        ))
+    }
+
+    fn arrange(&mut self, area: Rect, items: &[LayoutItem]) -> Option<Vec<Rect>> {
+        let properties = self.properties();
+
+        Some(arrange_box(
+            Axis::Vertical,
+            area,
+            properties.get_value(PROPERTY_SPACING),
+            Alignment::from_properties(properties),
+            items,
+        ))
+    }
+}
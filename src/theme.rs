@@ -0,0 +1,150 @@
+// Pushrod Widgets
+// Theme
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::properties::{is_transient, PropertyValue, WidgetProperties};
+use std::collections::HashMap;
+
+/// This is a selector that determines which `Widget`s a `Theme` rule applies to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Selector {
+    /// Matches every `Widget`.
+    Any,
+
+    /// Matches `Widget`s by their `widget_type`.
+    Type(String),
+
+    /// Matches a `Widget` by its name.
+    Name(String),
+
+    /// Matches `Widget`s whose name starts with the given prefix.
+    NamePrefix(String),
+
+    /// Matches `Widget`s that have the given boolean property (ie. `PROPERTY_HOVERED` or
+    /// `PROPERTY_DISABLED`) set to `true`.
+    State(u32),
+
+    /// Matches `Widget`s that match all of the given selectors.
+    All(Vec<Selector>),
+}
+
+impl Selector {
+    /// Determines whether or not the `Widget` described matches this selector.  State is read from
+    /// the properties set on the `Widget`, never from values supplied by a `Theme`.
+    pub fn matches(&self, widget_type: &str, name: &str, properties: &WidgetProperties) -> bool {
+        match self {
+            Selector::Any => true,
+            Selector::Type(selector_type) => selector_type == widget_type,
+            Selector::Name(selector_name) => selector_name == name,
+            Selector::NamePrefix(prefix) => name.starts_with(prefix.as_str()),
+            Selector::State(property_key) => {
+                properties.get_local(*property_key) == Some(&PropertyValue::Bool(true))
+            }
+            Selector::All(selectors) => selectors
+                .iter()
+                .all(|selector| selector.matches(widget_type, name, properties)),
+        }
+    }
+
+    /// Determines whether or not this selector depends on the state stored in the given property.
+    pub fn uses_state(&self, property_key: u32) -> bool {
+        match self {
+            Selector::State(state_key) => *state_key == property_key,
+            Selector::All(selectors) => selectors
+                .iter()
+                .any(|selector| selector.uses_state(property_key)),
+            _ => false,
+        }
+    }
+}
+
+struct ThemeRule {
+    selector: Selector,
+    properties: WidgetProperties,
+}
+
+/// This is a `Theme`, which is a list of rules that map a `Selector` to a set of properties.  A
+/// `WidgetCache` applies its `Theme` to each `Widget` as it is added, and again whenever the
+/// `Theme` is changed, or the state of a `Widget` used by a selector changes.
+#[derive(Default)]
+pub struct Theme {
+    rules: Vec<ThemeRule>,
+}
+
+/// This is the implementation of the `Theme`.  Rules are applied in the order in which they were
+/// added, so properties from a later matching rule take precedence over properties from an
+/// earlier matching rule.  Properties set directly on a `Widget` always take precedence over the
+/// `Theme`.
+impl Theme {
+    /// Adds a rule that applies the given properties to all `Widget`s matched by the `selector`.
+    pub fn add_rule(&mut self, selector: Selector, properties: WidgetProperties) {
+        self.rules.push(ThemeRule {
+            selector,
+            properties,
+        });
+    }
+
+    /// Resolves the properties this `Theme` supplies for the `Widget` described.  Transient
+    /// properties stored in a rule, such as the invalidated flag set when a color is stored, are
+    /// skipped.
+    pub fn resolve(
+        &self,
+        widget_type: &str,
+        name: &str,
+        properties: &WidgetProperties,
+    ) -> HashMap<u32, PropertyValue> {
+        let mut resolved = HashMap::new();
+
+        for rule in &self.rules {
+            if rule.selector.matches(widget_type, name, properties) {
+                for (property_key, property_value) in rule.properties.iter() {
+                    if !is_transient(*property_key) {
+                        resolved.insert(*property_key, property_value.clone());
+                    }
+                }
+            }
+        }
+
+        resolved
+    }
+
+    /// Determines whether or not any of the rules depend on the state stored in the given property.
+    pub fn uses_state(&self, property_key: u32) -> bool {
+        self.rules
+            .iter()
+            .any(|rule| rule.selector.uses_state(property_key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::properties::{PROPERTY_INVALIDATED, PROPERTY_MAIN_COLOR};
+    use sdl2::pixels::Color;
+
+    #[test]
+    fn resolve_skips_transient_properties() {
+        let mut properties = WidgetProperties::default();
+        let mut theme = Theme::default();
+
+        properties.set_color(PROPERTY_MAIN_COLOR, Color::RGB(255, 0, 0));
+        assert!(properties.key_set(PROPERTY_INVALIDATED));
+        theme.add_rule(Selector::Any, properties);
+
+        let resolved = theme.resolve("base", "button", &WidgetProperties::default());
+
+        assert!(resolved.contains_key(&PROPERTY_MAIN_COLOR));
+        assert!(!resolved.contains_key(&PROPERTY_INVALIDATED));
+    }
+}
@@ -14,7 +14,14 @@
 // limitations under the License.
 
 use crate::caches::TextureCache;
-use crate::properties::{WidgetProperties, PROPERTY_INVALIDATED};
+use crate::event::{EventContext, WidgetEvent};
+use crate::layout::{LayoutItem, Size};
+use crate::properties::{
+    PropertyValue, WidgetProperties, PROPERTY_INVALIDATED, PROPERTY_MAX_SIZE, PROPERTY_MIN_SIZE,
+    PROPERTY_PREFERRED_SIZE,
+};
+use crate::property_registry::{invalidation_of, Invalidation};
+use sdl2::rect::Rect;
 use sdl2::render::{Canvas, Texture};
 use sdl2::video::Window;
 
@@ -26,6 +33,13 @@ pub trait Widget {
     /// in the structure of the `Widget`, as they allow for direct manipulation of the properties.
     fn properties(&mut self) -> &mut WidgetProperties;
 
+    /// Returns the type name of this `Widget`, which is used to identify the kind of `Widget` when
+    /// a `WidgetCache` is exported, so that it can be constructed again when the tree is imported.
+    /// Custom `Widget`s should override this with a name that is unique to the `Widget`.
+    fn widget_type(&self) -> &'static str {
+        "widget"
+    }
+
     /// Draws the widget.  If you wish to modify the canvas object, you must declare it as `mut` in
     /// your implementation (ie `fn draw(&mut self, mut canvas: Canvas<Window>)`).  The `_canvas`
     /// is the currently active drawing canvas at the time this function is called.  This called
@@ -46,10 +60,97 @@ pub trait Widget {
         None
     }
 
-    /// Sets a property for a `Widget`.
-    fn set_property(&mut self, property_key: u32, property_value: String) {
-        self.properties().set(property_key, property_value);
-        // Send signal that a property changed to the engine.
+    /// Returns the size this `Widget` needs, given the `available` size of the area it is placed
+    /// in.  `Widget`s that draw content, such as text or images, should override this to report
+    /// the size of their content.  The default implementation returns `PROPERTY_PREFERRED_SIZE`
+    /// if it has been set, otherwise, the current size of the `Widget`.
+    fn measure(&mut self, _available: Size) -> Size {
+        let properties = self.properties();
+
+        properties
+            .try_get_pair(PROPERTY_PREFERRED_SIZE)
+            .unwrap_or_else(|_| properties.get_bounds())
+    }
+
+    /// Returns the smallest size this `Widget` can be given by a container.  The default
+    /// implementation returns `PROPERTY_MIN_SIZE` if it has been set, otherwise, `0x0`.
+    fn min_size(&mut self) -> Size {
+        self.properties()
+            .try_get_pair(PROPERTY_MIN_SIZE)
+            .unwrap_or((0, 0))
+    }
+
+    /// Returns the largest size this `Widget` can be given by a container.  The default
+    /// implementation returns `PROPERTY_MAX_SIZE` if it has been set, otherwise, an unlimited
+    /// size.
+    fn max_size(&mut self) -> Size {
+        self.properties()
+            .try_get_pair(PROPERTY_MAX_SIZE)
+            .unwrap_or((u32::MAX, u32::MAX))
+    }
+
+    /// Returns the size this `Widget` needs to fit its children, where `items` describes each of
+    /// the visible children, measured within the `available` size less this `Widget`'s border and
+    /// padding.  Containers override this along with `arrange`.  The default implementation
+    /// returns `None`, so the `Widget` is measured by `measure` instead.
+    fn measure_children(&mut self, _available: Size, _items: &[LayoutItem]) -> Option<Size> {
+        None
+    }
+
+    /// Sizes and places the children of this `Widget` within `area`, which is the `Widget`'s
+    /// content area.  `items` describes each of the visible children, in the order they were added.
+    /// Containers return a bounding `Rect` for each item, in the same order, which the
+    /// `WidgetCache` sets as each child's origin and size during its layout pass.  The default
+    /// implementation returns `None`, leaving the children where they were placed.
+    fn arrange(&mut self, _area: Rect, _items: &[LayoutItem]) -> Option<Vec<Rect>> {
+        None
+    }
+
+    /// Handles an input event sent to this `Widget`, where `ctx` describes the `Widget` handling
+    /// the event, and the `Widget` it is targeted at.  Returns `true` if the event was consumed,
+    /// so that it is not passed on to any other `Widget`.  The default implementation ignores all
+    /// events, returning `false`.
+    fn handle_event(&mut self, _event: &WidgetEvent, _ctx: &mut EventContext) -> bool {
+        false
+    }
+
+    /// Sets a property for a `Widget`.  If the stored value changes as a result, the
+    /// `on_property_changed` hook is called with the previous and new values.
+    fn set_property(&mut self, property_key: u32, property_value: PropertyValue) {
+        let old_value = self.properties().get_local(property_key).cloned();
+
+        if old_value.as_ref() != Some(&property_value) {
+            self.properties().set(property_key, property_value.clone());
+            self.on_property_changed(property_key, old_value.as_ref(), Some(&property_value));
+        }
+    }
+
+    /// Deletes a property for a `Widget`.  If the property was set, the `on_property_changed` hook
+    /// is called with the previous value, and `None` as the new value.
+    fn delete_property(&mut self, property_key: u32) {
+        let old_value = self.properties().get_local(property_key).cloned();
+
+        if old_value.is_some() {
+            self.properties().delete(property_key);
+            self.on_property_changed(property_key, old_value.as_ref(), None);
+        }
+    }
+
+    /// Called after a property value for this `Widget` has changed through `set_property` or
+    /// `delete_property`.  `None` indicates that the property was not set before the change, or
+    /// has been deleted by it.  The default implementation invalidates the `Widget` unless the
+    /// property was registered with `Invalidation::None` (such as the origin, as moving a `Widget`
+    /// does not require a repaint.)  Override this to decide which changes require the `Widget` to
+    /// be redrawn.
+    fn on_property_changed(
+        &mut self,
+        property_key: u32,
+        _old_value: Option<&PropertyValue>,
+        _new_value: Option<&PropertyValue>,
+    ) {
+        if invalidation_of(property_key) != Invalidation::None {
+            self.invalidate();
+        }
     }
 
     /// Set the invalidation key for this `Widget`, indicating that the `TextureCache` needs to
@@ -59,8 +160,9 @@ pub trait Widget {
     }
 
     /// Flag indicating whether or not the `draw` method needs to be called for this `Widget` so
-    /// that its `TextureCache` is refreshed.
+    /// that its `TextureCache` is refreshed.  Only the `Widget`'s own properties are checked, as
+    /// the flag is never supplied by a `Theme` or inherited.
     fn invalidated(&mut self) -> bool {
-        self.properties().key_set(PROPERTY_INVALIDATED)
+        self.properties().get_local(PROPERTY_INVALIDATED).is_some()
     }
 }
@@ -0,0 +1,72 @@
+// Pushrod Widgets
+// Widget Registry
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::system_widgets::base_widget::BaseWidget;
+use crate::system_widgets::grid::Grid;
+use crate::system_widgets::hbox::HBox;
+use crate::system_widgets::stack::Stack;
+use crate::system_widgets::vbox::VBox;
+use crate::widget::Widget;
+use std::collections::HashMap;
+
+/// This is a function that constructs a new `Widget` for a registered type name.
+pub type WidgetConstructor = Box<dyn Fn() -> Box<dyn Widget>>;
+
+/// This is a store that maps `Widget` type names to the functions that construct them.
+pub struct WidgetRegistry {
+    constructors: HashMap<String, WidgetConstructor>,
+}
+
+/// Default implementation for the `WidgetRegistry`, registering the system `Widget`s.
+impl Default for WidgetRegistry {
+    fn default() -> Self {
+        let mut registry = Self {
+            constructors: HashMap::new(),
+        };
+
+        registry.register("base", Box::new(|| Box::new(BaseWidget::default())));
+        registry.register("hbox", Box::new(|| Box::new(HBox::default())));
+        registry.register("vbox", Box::new(|| Box::new(VBox::default())));
+        registry.register("grid", Box::new(|| Box::new(Grid::default())));
+        registry.register("stack", Box::new(|| Box::new(Stack::default())));
+
+        registry
+    }
+}
+
+/// The `WidgetRegistry` is used to construct `Widget`s by their type name, such as when a scene
+/// file is loaded, or a `WidgetCache` is imported.  The type name should match the name returned
+/// by the `Widget`'s `widget_type` function.
+impl WidgetRegistry {
+    /// Registers a constructor for the given `Widget` type name, replacing any constructor that
+    /// was previously registered with the same name.
+    pub fn register(&mut self, widget_type: &str, constructor: WidgetConstructor) {
+        self.constructors
+            .insert(String::from(widget_type), constructor);
+    }
+
+    /// Returns a flag indicating whether or not a constructor has been registered for the type
+    /// name.
+    pub fn contains(&self, widget_type: &str) -> bool {
+        self.constructors.contains_key(widget_type)
+    }
+
+    /// Constructs a new `Widget` of the given type name, or `None` if the type is not registered.
+    pub fn create(&self, widget_type: &str) -> Option<Box<dyn Widget>> {
+        self.constructors
+            .get(widget_type)
+            .map(|constructor| constructor())
+    }
+}